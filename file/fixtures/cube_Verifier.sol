@@ -0,0 +1,49 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.24;
+
+/// @title Groth16 verifier over BLS12-381
+/// @notice Generated by bellman-example2. Needs the EIP-2537 precompiles.
+contract CubeVerifier {
+    /// Order of the BLS12-381 scalar field.
+    uint256 constant R = 0x73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001;
+    address constant G1_MSM = address(0x0c);
+    address constant PAIRING_CHECK = address(0x0f);
+
+    uint256 constant N_PUBLIC = 1;
+
+    bytes constant ALPHA = hex"0000000000000000000000000000000013e4bda83cc3d79d4943a6b20b79935d3cde32578bc4f8831ed67ab09fcce3ff5b724e610db7ae2366be5905d29e5faa00000000000000000000000000000000072b35f61b52412fbbdcd3fe251624e3a0ebf4a6c0bafd9996b82a70ac7e85ffb3845d9eb22f75fa8a01125eeb344d10";
+    bytes constant NEG_BETA = hex"00000000000000000000000000000000057de665201d2ad5977659084075b58c31afd9f8d36e1fdb99adf1296e9f6f233e20f32cdd61d863117919c686dc751a0000000000000000000000000000000017e85f3514b84fa3f85f69b288a7e0b6a373a9149770e279c65b2b0f64aec2e25b70bfced30c7f3fddac4abd5b7a1f1d000000000000000000000000000000000566c9e151f52d4d5ffda745f61a34fd70d923a973d2aac4974fb903ac38e3673e2e5463bbe77779743eba6a9a81ccc00000000000000000000000000000000006353fe2c18747cfccb5f8bd14a992d3b19be6ea110f6fe49468b1520c20484f5492b06ee0fcfe75bc5d56da56d51b6d";
+    bytes constant NEG_GAMMA = hex"0000000000000000000000000000000014f78fdf97061520f4c2c92c9883f295b4eb7208fce24d33be71fb7049ab996bc6ae567d1aa27d1daed0ea8615f5f1240000000000000000000000000000000019ed9f57cee18cdae9b01b330207d8a1d8b57939e535ffdc76118e4bbaced7689341b0975e92c0ea082bf7ef7036b295000000000000000000000000000000001333d5b6b665da40ff80babd661f0b419ceb35febeb96f8ed097ebb49d5a1aa37bb64b1024508a09958bdacc93b2a4fa000000000000000000000000000000001658353cf40683ddbf5b7caedb49f35d943b83cdf5127be50079380c26de6d7b7442f5a4d03599eceeee6d94c1d7d014";
+    bytes constant NEG_DELTA = hex"00000000000000000000000000000000114f3d1492bba356731f737bd990bfb261a1eb27555e81926c27d88d9e8234e3c9bc65f281e2242cdc93a9fd7b60ffab00000000000000000000000000000000135b64c33c3bbb145561a30b339e310e56948b70be8a656d6cc4e9a3c7a31a34d62a91cffa347ebd6c1a75ea2b1cea010000000000000000000000000000000012194dc1b768b7a57c02af8d2b47a80c765cc591bd0e56d121d9ce3be71fdeb1a3d88a91136a5debaf8abd1b154d8e95000000000000000000000000000000000fadd5965aba1121f38fd1a6189ad4d6547ef1bc2f03c6385ebfe00f7fb19c30a1c1d9584e5163c86b7eefdd923bf89c";
+    bytes constant IC0 = hex"000000000000000000000000000000000545a1a0db37035985d9a103393c625104499e5e11793b71029e7401e7e3257ed03070ff74731c11048d1d16a417bd7000000000000000000000000000000000029e6e657e94df54823d95a10a401389f0be1987379f0a3b10833df0fef209b2476ca20e109955af6ddda5a24039b671";
+    bytes constant IC1 = hex"000000000000000000000000000000000b7829d01c2830ff74df3a22afee6a45c0b77e7d616f28f4ee17cdd1daa9b873a57552ac1c3baacfae80785245386655000000000000000000000000000000000d85841d43703b12637ceb18d663c667944c7f5825317126e7aafd3794001ee5c00803d95d91de52d74360c433de2df9";
+
+    /// @param proof A (128 bytes), B (256 bytes) and C (128 bytes) in EIP-2537 encoding
+    /// @param input The public inputs, each below R
+    function verifyProof(bytes calldata proof, uint256[] calldata input) public view returns (bool) {
+        require(proof.length == 512, "bad proof length");
+        require(input.length == N_PUBLIC, "bad input length");
+        for (uint256 i = 0; i < N_PUBLIC; i++) {
+            require(input[i] < R, "input not in field");
+        }
+
+        bytes memory msmInput = bytes.concat(
+            IC0, bytes32(uint256(1)),
+            IC1, bytes32(input[0])
+        );
+        (bool ok, bytes memory vkX) = G1_MSM.staticcall(msmInput);
+        if (!ok || vkX.length != 128) {
+            return false;
+        }
+
+        bytes memory pairingInput = bytes.concat(
+            proof[0:128], proof[128:384],
+            ALPHA, NEG_BETA,
+            vkX, NEG_GAMMA,
+            proof[384:512], NEG_DELTA
+        );
+        bytes memory out;
+        (ok, out) = PAIRING_CHECK.staticcall(pairingInput);
+        return ok && out.length == 32 && abi.decode(out, (uint256)) == 1;
+    }
+}
@@ -0,0 +1,126 @@
+use bellman::groth16::{batch, prepare_verifying_key, verify_proof, Proof, VerifyingKey};
+use bls12_381::{Bls12, Scalar};
+use rand::{CryptoRng, RngCore};
+
+/// Verifies proofs of any number of circuits together. Proofs are grouped
+/// by verifying key and each group is checked with one batch verification;
+/// only when a batch fails are its proofs checked one by one, to find out
+/// which of them are invalid.
+#[derive(Default)]
+pub struct BatchVerifier<'a> {
+    proofs: Vec<(Proof<Bls12>, Vec<Scalar>)>,
+    /// Each distinct key with the positions of the proofs made for it.
+    groups: Vec<(&'a VerifyingKey<Bls12>, Vec<usize>)>,
+}
+
+impl<'a> BatchVerifier<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a proof of the circuit behind `vk` and returns its position,
+    /// by which `verify` reports it.
+    pub fn queue(
+        &mut self,
+        vk: &'a VerifyingKey<Bls12>,
+        proof: Proof<Bls12>,
+        public: Vec<Scalar>,
+    ) -> usize {
+        let index = self.proofs.len();
+        self.proofs.push((proof, public));
+        match self.groups.iter_mut().find(|(key, _)| *key == vk) {
+            Some((_, indices)) => indices.push(index),
+            None => self.groups.push((vk, vec![index])),
+        }
+        index
+    }
+
+    pub fn len(&self) -> usize {
+        self.proofs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.proofs.is_empty()
+    }
+
+    /// Number of distinct verifying keys, i.e. of batch verifications.
+    pub fn num_groups(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Returns the positions of the proofs that do not verify, in queue
+    /// order; empty if all of them do.
+    pub fn verify<R: RngCore + CryptoRng>(self, rng: &mut R) -> Vec<usize> {
+        let mut invalid = vec![];
+        for (vk, indices) in &self.groups {
+            let mut batch = batch::Verifier::<Bls12>::new();
+            for &i in indices {
+                let (proof, public) = &self.proofs[i];
+                batch.queue((proof.clone(), public.clone()));
+            }
+            if batch.verify(&mut *rng, vk).is_ok() {
+                continue;
+            }
+
+            let pvk = prepare_verifying_key(vk);
+            for &i in indices {
+                let (proof, public) = &self.proofs[i];
+                if verify_proof(&pvk, proof, public).is_err() {
+                    invalid.push(i);
+                }
+            }
+        }
+        invalid.sort_unstable();
+        invalid
+    }
+}
+
+#[test]
+fn test_batch_verifier() {
+    use crate::registry;
+    use crate::rng::seeded;
+
+    let mut rng = seeded(0);
+    let cube = registry::lookup("cube").unwrap();
+    let mimc = registry::lookup("mimc").unwrap();
+    let cube_params = cube.parameters(&mut rng).unwrap();
+    let mimc_params = mimc.parameters(&mut rng).unwrap();
+
+    let mut cube_proofs = vec![];
+    for _ in 0..3 {
+        let witness = cube.sample_witness(&mut rng);
+        cube_proofs.push(cube.prove(&cube_params, &witness, &mut rng).unwrap());
+    }
+    let witness = mimc.sample_witness(&mut rng);
+    let (mimc_proof, mimc_public) = mimc.prove(&mimc_params, &witness, &mut rng).unwrap();
+
+    let queue_all = |invalid: &[usize]| {
+        let mut verifier = BatchVerifier::new();
+        for (i, (proof, public)) in cube_proofs.iter().enumerate() {
+            let mut public = public.clone();
+            if invalid.contains(&i) {
+                public[0] += Scalar::one();
+            }
+            verifier.queue(&cube_params.vk, proof.clone(), public);
+        }
+        let i = verifier.queue(&mimc_params.vk, mimc_proof.clone(), mimc_public.clone());
+        assert_eq!(i, 3);
+        // A cube proof checked against the MiMC key.
+        let i = verifier.queue(
+            &mimc_params.vk,
+            cube_proofs[0].0.clone(),
+            mimc_public.clone(),
+        );
+        assert_eq!(i, 4);
+        verifier
+    };
+
+    let verifier = queue_all(&[]);
+    assert_eq!(verifier.len(), 5);
+    assert_eq!(verifier.num_groups(), 2);
+    assert_eq!(verifier.verify(&mut rng), [4]);
+
+    assert_eq!(queue_all(&[0, 2]).verify(&mut rng), [0, 2, 4]);
+
+    assert!(BatchVerifier::new().verify(&mut rng).is_empty());
+}
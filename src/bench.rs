@@ -0,0 +1,260 @@
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use bellman::groth16::{batch, prepare_verifying_key, verify_proof, Proof};
+use bls12_381::Bls12;
+use rand::{CryptoRng, RngCore};
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+use crate::registry::CircuitSpec;
+
+/// How many times to repeat each operation.
+#[derive(Clone, Copy, Debug)]
+pub struct BenchConfig {
+    /// Repetitions of proving, verification, serialization and batch
+    /// verification.
+    pub samples: usize,
+    /// Repetitions of parameter generation, which is much slower.
+    pub setup_samples: usize,
+    /// Proofs per batch verification.
+    pub batch_size: usize,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        BenchConfig {
+            samples: 10,
+            setup_samples: 1,
+            batch_size: 10,
+        }
+    }
+}
+
+/// Timings of one operation on one circuit, in seconds.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Measurement {
+    pub circuit: String,
+    pub operation: String,
+    pub samples: usize,
+    /// Proofs handled by each sample: the batch size for `batch_verify`,
+    /// one otherwise.
+    pub proofs: usize,
+    pub mean: f64,
+    pub median: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Measurement {
+    fn new(circuit: &str, operation: &str, proofs: usize, times: &[Duration]) -> Self {
+        let mut secs: Vec<f64> = times.iter().map(Duration::as_secs_f64).collect();
+        secs.sort_by(f64::total_cmp);
+
+        let n = secs.len();
+        let median = match n {
+            0 => 0.0,
+            _ if n % 2 == 1 => secs[n / 2],
+            _ => (secs[n / 2 - 1] + secs[n / 2]) / 2.0,
+        };
+
+        Measurement {
+            circuit: circuit.to_string(),
+            operation: operation.to_string(),
+            samples: n,
+            proofs,
+            mean: if n == 0 {
+                0.0
+            } else {
+                secs.iter().sum::<f64>() / n as f64
+            },
+            median,
+            min: secs.first().copied().unwrap_or(0.0),
+            max: secs.last().copied().unwrap_or(0.0),
+        }
+    }
+}
+
+fn time<T>(f: impl FnOnce() -> Result<T>) -> Result<(T, Duration)> {
+    let start = Instant::now();
+    let out = f()?;
+    Ok((out, start.elapsed()))
+}
+
+/// Benchmarks parameter generation, proving, verification, proof
+/// serialization and batch verification of `spec`, each on fresh random
+/// witnesses. Fails with `Error::InvalidFormat` if any count in `config`
+/// is zero.
+pub fn run<R: RngCore + CryptoRng>(
+    spec: &dyn CircuitSpec,
+    config: &BenchConfig,
+    rng: &mut R,
+) -> Result<Vec<Measurement>> {
+    if config.samples == 0 || config.setup_samples == 0 || config.batch_size == 0 {
+        return Err(Error::InvalidFormat(format!(
+            "benchmarks need at least one sample, got {:?}",
+            config
+        )));
+    }
+    let name = spec.name();
+
+    let mut setup = vec![];
+    let mut params = None;
+    for _ in 0..config.setup_samples {
+        let (p, t) = time(|| spec.parameters(rng))?;
+        setup.push(t);
+        params = Some(p);
+    }
+    let params = params.unwrap();
+    let pvk = prepare_verifying_key(&params.vk);
+
+    let (mut prove, mut verify) = (vec![], vec![]);
+    let (mut serialize, mut deserialize) = (vec![], vec![]);
+    let mut proofs = vec![];
+    for _ in 0..config.samples {
+        let witness = spec.sample_witness(rng);
+        let ((proof, public), t) = time(|| spec.prove(&params, &witness, rng))?;
+        prove.push(t);
+
+        let ((), t) = time(|| Ok(verify_proof(&pvk, &proof, &public)?))?;
+        verify.push(t);
+
+        let mut proof_vec = vec![];
+        let ((), t) = time(|| Ok(proof.write(&mut proof_vec)?))?;
+        serialize.push(t);
+        let (read, t) = time(|| Ok(Proof::<Bls12>::read(&proof_vec[..])?))?;
+        deserialize.push(t);
+        if read != proof {
+            return Err(Error::InvalidFormat(format!(
+                "{}: proof changed in serialization",
+                name
+            )));
+        }
+
+        proofs.push((proof, public));
+    }
+
+    let mut batch_verify = vec![];
+    for _ in 0..config.samples {
+        let ((), t) = time(|| {
+            let mut batch = batch::Verifier::<Bls12>::new();
+            for (proof, public) in proofs.iter().cycle().take(config.batch_size) {
+                batch.queue((proof, &public[..]));
+            }
+            Ok(batch.verify(&mut *rng, &params.vk)?)
+        })?;
+        batch_verify.push(t);
+    }
+
+    Ok(vec![
+        Measurement::new(name, "setup", 1, &setup),
+        Measurement::new(name, "prove", 1, &prove),
+        Measurement::new(name, "verify", 1, &verify),
+        Measurement::new(name, "batch_verify", config.batch_size, &batch_verify),
+        Measurement::new(name, "serialize", 1, &serialize),
+        Measurement::new(name, "deserialize", 1, &deserialize),
+    ])
+}
+
+pub fn write_json<W: Write>(measurements: &[Measurement], mut writer: W) -> Result<()> {
+    serde_json::to_writer_pretty(&mut writer, measurements)?;
+    writeln!(writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn write_csv<W: Write>(measurements: &[Measurement], mut writer: W) -> Result<()> {
+    writeln!(
+        writer,
+        "circuit,operation,samples,proofs,mean,median,min,max"
+    )?;
+    for m in measurements {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{},{}",
+            m.circuit, m.operation, m.samples, m.proofs, m.mean, m.median, m.min, m.max
+        )?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[test]
+fn test_bench() {
+    use crate::registry;
+    use crate::rng::seeded;
+
+    let config = BenchConfig {
+        samples: 3,
+        setup_samples: 1,
+        batch_size: 4,
+    };
+    let measurements = run(
+        &*registry::lookup("cube").unwrap(),
+        &config,
+        &mut seeded(0),
+    )
+    .unwrap();
+
+    let operations: Vec<_> = measurements.iter().map(|m| m.operation.as_str()).collect();
+    assert_eq!(
+        operations,
+        [
+            "setup",
+            "prove",
+            "verify",
+            "batch_verify",
+            "serialize",
+            "deserialize"
+        ]
+    );
+    for m in &measurements {
+        assert_eq!(m.circuit, "cube");
+        assert!(m.min <= m.median && m.median <= m.max);
+    }
+    assert_eq!(measurements[0].samples, 1);
+    assert_eq!(measurements[1].samples, 3);
+    assert_eq!(measurements[3].proofs, 4);
+
+    let mut csv = vec![];
+    write_csv(&measurements, &mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    assert_eq!(csv.lines().count(), 1 + measurements.len());
+    assert!(csv.lines().nth(2).unwrap().starts_with("cube,prove,3,1,"));
+
+    let mut json = vec![];
+    write_json(&measurements, &mut json).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(json[3]["operation"], "batch_verify");
+
+    // Nothing to measure is an error rather than a panic.
+    for config in [
+        BenchConfig {
+            samples: 0,
+            ..config
+        },
+        BenchConfig {
+            setup_samples: 0,
+            ..config
+        },
+        BenchConfig {
+            batch_size: 0,
+            ..config
+        },
+    ] {
+        assert!(matches!(
+            run(&*registry::lookup("cube").unwrap(), &config, &mut seeded(0)),
+            Err(Error::InvalidFormat(_))
+        ));
+    }
+}
+
+#[test]
+fn test_measurement_stats() {
+    let times = [3, 1, 4, 2].map(Duration::from_secs);
+    let m = Measurement::new("c", "op", 1, &times);
+    assert_eq!((m.mean, m.median, m.min, m.max), (2.5, 2.5, 1.0, 4.0));
+
+    let m = Measurement::new("c", "op", 1, &times[..3]);
+    assert_eq!(m.median, 3.0);
+}
@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use bellman::{Circuit, ConstraintSystem, Index, LinearCombination, SynthesisError, Variable};
+use bls12_381::Scalar;
+
+use crate::encode::scalar_to_decimal;
+use crate::error::Result;
+use crate::inspect::compute_path;
+
+/// The first constraint `A * B = C` that a witness does not satisfy, with
+/// the values of its three linear combinations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Unsatisfied {
+    pub name: String,
+    pub a: Scalar,
+    pub b: Scalar,
+    pub c: Scalar,
+}
+
+impl fmt::Display for Unsatisfied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}` is not satisfied: A = {}, B = {}, C = {}, but A * B = {}",
+            self.name,
+            scalar_to_decimal(&self.a),
+            scalar_to_decimal(&self.b),
+            scalar_to_decimal(&self.c),
+            scalar_to_decimal(&(self.a * self.b))
+        )
+    }
+}
+
+/// A `ConstraintSystem` that evaluates every constraint on the witness as
+/// it is enforced, and remembers the first one that does not hold.
+pub struct Checker {
+    inputs: Vec<Scalar>,
+    aux: Vec<Scalar>,
+    overrides: HashMap<String, Scalar>,
+    namespace: Vec<String>,
+    num_constraints: usize,
+    unsatisfied: Option<Unsatisfied>,
+}
+
+impl Default for Checker {
+    fn default() -> Self {
+        Checker {
+            inputs: vec![Scalar::one()],
+            aux: vec![],
+            overrides: HashMap::new(),
+            namespace: vec![],
+            num_constraints: 0,
+            unsatisfied: None,
+        }
+    }
+}
+
+/// Synthesizes `circuit` with its witness and returns the first
+/// constraint it does not satisfy, if any.
+pub fn check<C: Circuit<Scalar>>(circuit: C) -> Result<Option<Unsatisfied>> {
+    let mut checker = Checker::new();
+    circuit.synthesize(&mut checker)?;
+    Ok(checker.unsatisfied)
+}
+
+impl Checker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Assigns `value` to the variable at `path` instead of what the
+    /// circuit computes, as a witness generator with a bug would.
+    pub fn set_override(&mut self, path: &str, value: Scalar) {
+        self.overrides.insert(path.to_string(), value);
+    }
+
+    pub fn num_constraints(&self) -> usize {
+        self.num_constraints
+    }
+
+    pub fn is_satisfied(&self) -> bool {
+        self.unsatisfied.is_none()
+    }
+
+    pub fn unsatisfied(&self) -> Option<&Unsatisfied> {
+        self.unsatisfied.as_ref()
+    }
+
+    fn assign<F>(&self, annotation: String, f: F) -> std::result::Result<Scalar, SynthesisError>
+    where
+        F: FnOnce() -> std::result::Result<Scalar, SynthesisError>,
+    {
+        // Gadgets like `AllocatedNum` keep the value they compute, so `f`
+        // runs even when its result is replaced.
+        let value = f()?;
+        let path = compute_path(&self.namespace, annotation);
+        Ok(self.overrides.get(&path).copied().unwrap_or(value))
+    }
+
+    fn eval(&self, lc: &LinearCombination<Scalar>) -> Scalar {
+        lc.as_ref()
+            .iter()
+            .map(|(var, coeff)| {
+                let value = match var.get_unchecked() {
+                    Index::Input(i) => self.inputs[i],
+                    Index::Aux(i) => self.aux[i],
+                };
+                value * coeff
+            })
+            .sum()
+    }
+}
+
+impl ConstraintSystem<Scalar> for Checker {
+    type Root = Self;
+
+    fn alloc<F, A, AR>(
+        &mut self,
+        annotation: A,
+        f: F,
+    ) -> std::result::Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> std::result::Result<Scalar, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let value = self.assign(annotation().into(), f)?;
+        self.aux.push(value);
+        Ok(Variable::new_unchecked(Index::Aux(self.aux.len() - 1)))
+    }
+
+    fn alloc_input<F, A, AR>(
+        &mut self,
+        annotation: A,
+        f: F,
+    ) -> std::result::Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> std::result::Result<Scalar, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let value = self.assign(annotation().into(), f)?;
+        self.inputs.push(value);
+        Ok(Variable::new_unchecked(Index::Input(self.inputs.len() - 1)))
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, annotation: A, a: LA, b: LB, c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+        LB: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+        LC: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+    {
+        self.num_constraints += 1;
+        if self.unsatisfied.is_some() {
+            return;
+        }
+
+        let a = self.eval(&a(LinearCombination::zero()));
+        let b = self.eval(&b(LinearCombination::zero()));
+        let c = self.eval(&c(LinearCombination::zero()));
+        if a * b != c {
+            self.unsatisfied = Some(Unsatisfied {
+                name: compute_path(&self.namespace, annotation().into()),
+                a,
+                b,
+                c,
+            });
+        }
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.namespace.push(name_fn().into());
+    }
+
+    fn pop_namespace(&mut self) {
+        self.namespace.pop();
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+}
+
+#[test]
+fn test_check_cube() {
+    use crate::cube::CubeDemo;
+
+    let c = CubeDemo {
+        x: Some(Scalar::from(3u64)),
+    };
+    assert_eq!(check(c).unwrap(), None);
+
+    // No witness at all is an error rather than a failing constraint.
+    assert!(check(CubeDemo::<Scalar> { x: None }).is_err());
+
+    // x = 3 but x * x claimed to be 10.
+    let mut checker = Checker::new();
+    checker.set_override("tmp_1", Scalar::from(10u64));
+    CubeDemo {
+        x: Some(Scalar::from(3u64)),
+    }
+    .synthesize(&mut checker)
+    .unwrap();
+
+    assert_eq!(checker.num_constraints(), 3);
+    assert_eq!(
+        checker.unsatisfied(),
+        Some(&Unsatisfied {
+            name: "tmp_1".to_string(),
+            a: Scalar::from(3u64),
+            b: Scalar::from(3u64),
+            c: Scalar::from(10u64),
+        })
+    );
+    assert_eq!(
+        checker.unsatisfied().unwrap().to_string(),
+        "`tmp_1` is not satisfied: A = 3, B = 3, C = 10, but A * B = 9"
+    );
+}
+
+#[test]
+fn test_check_mimc() {
+    use ff::Field;
+
+    use crate::mimc::{MiMCDemo, MimcParams, MIMC_ROUNDS, MIMC_SEED};
+
+    let mimc_params = MimcParams::<Scalar>::from_keccak_chain(MIMC_SEED, MIMC_ROUNDS);
+    let demo = || MiMCDemo {
+        xl: Some(Scalar::from(1u64)),
+        xr: Some(Scalar::from(2u64)),
+        params: &mimc_params,
+    };
+    assert_eq!(check(demo()).unwrap(), None);
+
+    let mut checker = Checker::new();
+    checker.set_override(
+        "mimc/round 17/new_xl/num",
+        Scalar::random(&mut crate::rng::seeded(0)),
+    );
+    demo().synthesize(&mut checker).unwrap();
+
+    assert!(!checker.is_satisfied());
+    assert_eq!(
+        checker.unsatisfied().unwrap().name,
+        "mimc/round 17/new_xL = xR + (xL + Ci)^3"
+    );
+    assert_eq!(checker.num_constraints(), 2 * MIMC_ROUNDS);
+}
+
+#[test]
+fn test_check_expression() {
+    use crate::expr::{Expression, ExpressionCircuit};
+
+    let e = Expression::<Scalar>::parse("x^3 + x + 5 == out", &["out"]).unwrap();
+    let values = [("x", 3u64), ("out", 36)]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), Scalar::from(v)))
+        .collect();
+
+    let unsatisfied = check(ExpressionCircuit {
+        expression: &e,
+        values,
+    })
+    .unwrap()
+    .unwrap();
+    assert_eq!(unsatisfied.name, "lhs == rhs");
+    // (x^3 + x + 5 - out) * 1 = 0
+    assert_eq!(unsatisfied.a, -Scalar::one());
+    assert_eq!(unsatisfied.b, Scalar::one());
+    assert_eq!(unsatisfied.c, Scalar::zero());
+}
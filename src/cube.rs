@@ -2,7 +2,7 @@
 #![allow(unused_variables)]
 use bls12_381::{Bls12, Scalar};
 // For randomness (during paramgen and proof generation)
-use rand::{thread_rng, Rng};
+use rand::Rng;
 use ff::PrimeField as Fr;
 
 use bellman::{
@@ -23,6 +23,7 @@ use crate::encode;
 
 // proving that I know x such that x^3 + x + 5 == 35
 // Generalized: x^3 + x + 5 == out
+// For any other polynomial use `polynomial::PolynomialCircuit`.
 #[allow(clippy::upper_case_acronyms)]
 pub struct CubeDemo<E: Fr> {
     pub x: Option<E>,
@@ -63,8 +64,8 @@ impl <E: Fr> Circuit<E> for CubeDemo<E> {
         );
 
         // Allocate: tmp_1 * x = y
-        let x_cubed_val = tmp_1_val.map(|mut e| {
-            e.mul_assign(&x_val.unwrap());
+        let x_cubed_val = tmp_1_val.zip(x_val).map(|(mut e, x)| {
+            e.mul_assign(&x);
             e
         });
         let x_cubed = cs.alloc(|| "x_cubed", || {
@@ -80,16 +81,16 @@ impl <E: Fr> Circuit<E> for CubeDemo<E> {
 
         // Allocating the public "primary" output uses alloc_input
         let out = cs.alloc_input(|| "out", || {
-            let mut tmp = x_cubed_val.unwrap();
-            tmp.add_assign(&x_val.unwrap());
-            tmp.add_assign(&E::from_str_vartime("5").unwrap());
+            let mut tmp = x_cubed_val.ok_or(SynthesisError::AssignmentMissing)?;
+            tmp.add_assign(&x_val.ok_or(SynthesisError::AssignmentMissing)?);
+            tmp.add_assign(&E::from(5));
             Ok(tmp)
         })?;
         // tmp_2 + 5 = out
         // => (tmp_2 + 5) * 1 = out
         cs.enforce(
             || "out",
-            |lc| lc + x_cubed + x + (E::from_str_vartime("5").unwrap(), CS::one()),
+            |lc| lc + x_cubed + x + (E::from(5), CS::one()),
             |lc| lc + CS::one(),
             |lc| lc + out
         );
@@ -106,11 +107,30 @@ impl <E: Fr> Circuit<E> for CubeDemo<E> {
     }
 }
 
+/// Fresh parameters and a proof for x = 3, with its public input 35, for
+/// the tests of the file formats.
+#[cfg(test)]
+pub(crate) fn test_proof<R: rand::RngCore>(
+    rng: &mut R
+) -> (bellman::groth16::Parameters<Bls12>, Proof<Bls12>, Vec<Scalar>)
+{
+    let params = generate_random_parameters::<Bls12, _, _>(
+        CubeDemo::<Scalar> { x: None },
+        &mut *rng
+    ).unwrap();
+    let c = CubeDemo {
+        x: Some(Scalar::from(3u64))
+    };
+    let proof = create_random_proof(c, &params, rng).unwrap();
+
+    (params, proof, vec![Scalar::from(35u64)])
+}
+
 #[test]
 fn test_cube_proof(){
-    // This may not be cryptographically safe, use
-    // `OsRng` (for example) in production software.
-    let mut rng = thread_rng();
+    use crate::rng::seeded;
+
+    let mut rng = seeded(0);
 
     println!("Creating parameters...");
 
@@ -126,17 +146,6 @@ fn test_cube_proof(){
     // Prepare the verification key (for proof verification)
     let pvk = prepare_verifying_key(&params.vk);
 
-    // println!("alphag1{:?}", params.vk.alpha_g1.to_uncompressed());
-    // println!("betag1{:?}", params.vk.beta_g1.to_compressed());
-    // println!("betag2{:?}", params.vk.beta_g2.to_compressed());
-    // println!("gammag2{:?}", params.vk.gamma_g2.to_compressed());
-    // println!("deltag1{:?}", params.vk.delta_g1.to_compressed());
-    // println!("deltag2{:?}", params.vk.delta_g2.to_compressed());
-    // println!("ic0{:?}", params.vk.ic[0].to_compressed());
-    // println!("ic1{:?}", params.vk.ic[1].to_compressed());
-
-    println!(r#"{{"alpha_1":{:?},"beta_1":{:?},"beta_2":{:?},"gamma_2":{:?},"delta_1":{:?},"delta_2":{:?},"ic":[{:?},{:?}]}}"#, params.vk.alpha_g1.to_uncompressed(), params.vk.beta_g1.to_uncompressed(), params.vk.beta_g2.to_uncompressed(), params.vk.gamma_g2.to_uncompressed(), params.vk.delta_g1.to_uncompressed(), params.vk.delta_g2.to_uncompressed(), params.vk.ic[0].to_uncompressed(), params.vk.ic[1].to_uncompressed());
-    
     println!("Creating proofs...");
 
     // Create an instance of circuit
@@ -147,23 +156,18 @@ fn test_cube_proof(){
     // Create a groth16 proof with our parameters.
     let proof = create_random_proof(c, &params, &mut rng).unwrap();
 
-    let mut proof_vec = vec![];
-    proof.write(&mut proof_vec).unwrap();
-
-    let proof_a_affine = proof.a.to_uncompressed();
-    // println!("proofaaffine: {:?}", proof_a_affine);
-
-    let proof_b_affine = proof.b.to_uncompressed();
-    // println!("proofabffine: {:?}", proof_b_affine);
-
-    let proof_c_affine = proof.c.to_uncompressed();
-    // println!("proofacffine: {:?}", proof_c_affine);
-
-    println!(r#"{{"pi_a":{:?},"pi_b":{:?},"pi_c":{:?}}}"#, proof_a_affine, proof_b_affine, proof_c_affine);
-    let res_proof = format!(r#"{{"pi_a":{:?},"pi_b":{:?},"pi_c":{:?}}}"#, proof_a_affine, proof_b_affine, proof_c_affine);
-    let res_vkey = format!(r#"{{"alpha_1":{:?},"beta_1":{:?},"beta_2":{:?},"gamma_2":{:?},"delta_1":{:?},"delta_2":{:?},"ic":[{:?},{:?}]}}"#, params.vk.alpha_g1.to_uncompressed(), params.vk.beta_g1.to_uncompressed(), params.vk.beta_g2.to_uncompressed(), params.vk.gamma_g2.to_uncompressed(), params.vk.delta_g1.to_uncompressed(), params.vk.delta_g2.to_uncompressed(), params.vk.ic[0].to_uncompressed(), params.vk.ic[1].to_uncompressed());
-    encode::create_uncompressed_file(res_proof, res_vkey);
-    encode::encode_uncompressed();
+    let res_proof = encode::Proof::from(&proof);
+    let res_vkey = encode::VerifyKey::from(&params.vk);
+    println!("{}", serde_json::to_string(&res_proof).unwrap());
+    println!("{}", serde_json::to_string(&res_vkey).unwrap());
+    let dir = tempfile::tempdir().unwrap();
+    let files = encode::OutputFiles::new(dir.path(), "cube", "0");
+    encode::create_uncompressed_file(&files, &res_proof, &res_vkey).unwrap();
+    encode::encode_uncompressed(&files).unwrap();
+    assert_eq!(
+        encode::load_uncompressed_proof(files.proof_path("proof_uncompressed.json")).unwrap(),
+        proof
+    );
 
     assert!(verify_proof(
         &pvk,
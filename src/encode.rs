@@ -1,37 +1,199 @@
 use serde::{Deserialize, Serialize};
 
 use std::fs::File;
-use std::fs::OpenOptions;
 use std::io::BufReader;
+use std::io::Read;
 use std::io::Write;
-use std::io;
+use std::path::{Path, PathBuf};
+
+use bellman::groth16;
+use bls12_381::{Bls12, G1Affine, G2Affine, Scalar};
+use ff::PrimeField;
+use num_bigint::BigUint;
+
+use crate::error::{Error, Result};
+use crate::solidity;
 
 use core::fmt::Write as encode_write;
 
-#[derive(Serialize, Deserialize, Debug)]
-struct Proof {
-    pi_a: Vec<u8>,
-    pi_b: Vec<u8>,
-    pi_c: Vec<u8>,
+/// A Groth16 proof with each point as raw uncompressed bytes.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Proof {
+    pub pi_a: Vec<u8>,
+    pub pi_b: Vec<u8>,
+    pub pi_c: Vec<u8>,
+}
+
+/// A Groth16 verifying key with each point as raw uncompressed bytes.
+/// `ic` holds one point per public input, plus one for the constant term.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VerifyKey {
+    pub alpha_1: Vec<u8>,
+    pub beta_1: Vec<u8>,
+    pub beta_2: Vec<u8>,
+    pub gamma_2: Vec<u8>,
+    pub delta_1: Vec<u8>,
+    pub delta_2: Vec<u8>,
+    pub ic: Vec<Vec<u8>>,
+}
+
+impl From<&groth16::Proof<Bls12>> for Proof {
+    fn from(proof: &groth16::Proof<Bls12>) -> Self {
+        Proof {
+            pi_a: proof.a.to_uncompressed().to_vec(),
+            pi_b: proof.b.to_uncompressed().to_vec(),
+            pi_c: proof.c.to_uncompressed().to_vec(),
+        }
+    }
+}
+
+impl From<&groth16::VerifyingKey<Bls12>> for VerifyKey {
+    fn from(vk: &groth16::VerifyingKey<Bls12>) -> Self {
+        VerifyKey {
+            alpha_1: vk.alpha_g1.to_uncompressed().to_vec(),
+            beta_1: vk.beta_g1.to_uncompressed().to_vec(),
+            beta_2: vk.beta_g2.to_uncompressed().to_vec(),
+            gamma_2: vk.gamma_g2.to_uncompressed().to_vec(),
+            delta_1: vk.delta_g1.to_uncompressed().to_vec(),
+            delta_2: vk.delta_g2.to_uncompressed().to_vec(),
+            ic: vk.ic.iter().map(|p| p.to_uncompressed().to_vec()).collect(),
+        }
+    }
+}
+
+/// Decodes an uncompressed G1 point, rejecting points off the curve or
+/// outside the prime-order subgroup.
+fn g1_from_uncompressed(name: &str, bytes: &[u8]) -> Result<G1Affine> {
+    let bytes: &[u8; 96] = bytes.try_into().map_err(|_| {
+        Error::InvalidFormat(format!(
+            "{}: expected 96 bytes, found {}",
+            name,
+            bytes.len()
+        ))
+    })?;
+    Option::from(G1Affine::from_uncompressed(bytes))
+        .ok_or_else(|| Error::InvalidPoint(name.to_string()))
+}
+
+/// Decodes an uncompressed G2 point, rejecting points off the curve or
+/// outside the prime-order subgroup.
+fn g2_from_uncompressed(name: &str, bytes: &[u8]) -> Result<G2Affine> {
+    let bytes: &[u8; 192] = bytes.try_into().map_err(|_| {
+        Error::InvalidFormat(format!(
+            "{}: expected 192 bytes, found {}",
+            name,
+            bytes.len()
+        ))
+    })?;
+    Option::from(G2Affine::from_uncompressed(bytes))
+        .ok_or_else(|| Error::InvalidPoint(name.to_string()))
+}
+
+impl Proof {
+    pub fn to_groth16(&self) -> Result<groth16::Proof<Bls12>> {
+        Ok(groth16::Proof {
+            a: g1_from_uncompressed("pi_a", &self.pi_a)?,
+            b: g2_from_uncompressed("pi_b", &self.pi_b)?,
+            c: g1_from_uncompressed("pi_c", &self.pi_c)?,
+        })
+    }
+}
+
+impl VerifyKey {
+    pub fn to_groth16(&self) -> Result<groth16::VerifyingKey<Bls12>> {
+        if self.ic.is_empty() {
+            return Err(Error::InvalidFormat(
+                "ic: expected at least one point".to_string(),
+            ));
+        }
+
+        Ok(groth16::VerifyingKey {
+            alpha_g1: g1_from_uncompressed("alpha_1", &self.alpha_1)?,
+            beta_g1: g1_from_uncompressed("beta_1", &self.beta_1)?,
+            beta_g2: g2_from_uncompressed("beta_2", &self.beta_2)?,
+            gamma_g2: g2_from_uncompressed("gamma_2", &self.gamma_2)?,
+            delta_g1: g1_from_uncompressed("delta_1", &self.delta_1)?,
+            delta_g2: g2_from_uncompressed("delta_2", &self.delta_2)?,
+            ic: self
+                .ic
+                .iter()
+                .enumerate()
+                .map(|(i, p)| g1_from_uncompressed(&format!("ic[{}]", i), p))
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+/// Reads a proof in the `proof_uncompressed.json` layout.
+pub fn read_uncompressed_proof<R: Read>(reader: R) -> Result<groth16::Proof<Bls12>> {
+    let proof: Proof = serde_json::from_reader(reader)?;
+    proof.to_groth16()
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct VerifyKey {
-    alpha_1: Vec<u8>,
-    beta_1: Vec<u8>,
-    beta_2: Vec<u8>,
-    gamma_2: Vec<u8>,
-    delta_1: Vec<u8>,
-    delta_2: Vec<u8>,
-    ic: Vec<Vec<u8>>,
+/// Reads a verifying key in the `vkey_uncompressed.json` layout.
+pub fn read_uncompressed_vkey<R: Read>(reader: R) -> Result<groth16::VerifyingKey<Bls12>> {
+    let vkey: VerifyKey = serde_json::from_reader(reader)?;
+    vkey.to_groth16()
 }
 
-pub fn create_uncompressed_file(res_proof: String, res_vkey: String) -> Result<(), io::Error> {
-    let mut file_uncompressed_proof = File::create("./file/proof_uncompressed.json").unwrap();
-    file_uncompressed_proof.write(res_proof.as_bytes())?;
+pub fn load_uncompressed_proof<P: AsRef<Path>>(path: P) -> Result<groth16::Proof<Bls12>> {
+    read_uncompressed_proof(BufReader::new(File::open(path)?))
+}
+
+pub fn load_uncompressed_vkey<P: AsRef<Path>>(path: P) -> Result<groth16::VerifyingKey<Bls12>> {
+    read_uncompressed_vkey(BufReader::new(File::open(path)?))
+}
+
+/// Where the exporters put their files. The verifying key belongs to the
+/// circuit and is named `<circuit>_<file>`; everything tied to a single proof
+/// is named `<circuit>_<proof id>_<file>`, so several circuits and proofs can
+/// share one directory.
+#[derive(Debug, Clone)]
+pub struct OutputFiles {
+    pub dir: PathBuf,
+    pub circuit: String,
+    pub proof_id: String,
+}
+
+impl OutputFiles {
+    pub fn new<P: Into<PathBuf>>(dir: P, circuit: &str, proof_id: &str) -> Self {
+        OutputFiles {
+            dir: dir.into(),
+            circuit: circuit.to_string(),
+            proof_id: proof_id.to_string(),
+        }
+    }
+
+    pub fn vkey_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}_{}", self.circuit, name))
+    }
 
-    let mut file_uncompressed_vkey = File::create("./file/vkey_uncompressed.json").unwrap();
-    file_uncompressed_vkey.write(res_vkey.as_bytes())?;
+    pub fn proof_path(&self, name: &str) -> PathBuf {
+        self.dir
+            .join(format!("{}_{}_{}", self.circuit, self.proof_id, name))
+    }
+}
+
+pub fn write_uncompressed_proof<W: Write>(proof: &Proof, writer: W) -> Result<()> {
+    serde_json::to_writer(writer, proof)?;
+    Ok(())
+}
+
+pub fn write_uncompressed_vkey<W: Write>(vkey: &VerifyKey, writer: W) -> Result<()> {
+    serde_json::to_writer(writer, vkey)?;
+    Ok(())
+}
+
+pub fn create_uncompressed_file(
+    files: &OutputFiles,
+    proof: &Proof,
+    vkey: &VerifyKey,
+) -> Result<()> {
+    let file_uncompressed_proof = File::create(files.proof_path("proof_uncompressed.json"))?;
+    write_uncompressed_proof(proof, file_uncompressed_proof)?;
+
+    let file_uncompressed_vkey = File::create(files.vkey_path("vkey_uncompressed.json"))?;
+    write_uncompressed_vkey(vkey, file_uncompressed_vkey)?;
 
     Ok(())
 }
@@ -44,81 +206,628 @@ pub fn encode_hex(bytes: &[u8]) -> String {
     s
 }
 
-pub fn encode_uncompressed() -> Result<(), io::Error> {
-
-    let proof_file = File::open("./file/proof_uncompressed.json").unwrap();
-    let vkey_file = File::open("./file/vkey_uncompressed.json").unwrap();
-
-    // let file = OpenOptions::new().read(true);
-
-    let proof_reader = BufReader::new(proof_file);
-    let vkey_reader = BufReader::new(vkey_file);
-
-    let deserialized_proof: Proof = serde_json::from_reader(proof_reader).unwrap();
-    let deserialized_vkey: VerifyKey = serde_json::from_reader(vkey_reader).unwrap();
-
-    let pi_a = deserialized_proof.pi_a;
-    let pi_b = deserialized_proof.pi_b;
-    let pi_c = deserialized_proof.pi_c;
-    
-    let res_a = format!("{}{}", "0x", encode_hex(&pi_a));
-    let res_b = format!("{}{}", "0x", encode_hex(&pi_b));
-    let res_c = format!("{}{}", "0x", encode_hex(&pi_c));
-
-    let vkey_a = deserialized_vkey.alpha_1;
-    let vkey_b1 = deserialized_vkey.beta_1;
-    let vkey_b2 = deserialized_vkey.beta_2;
-    let vkey_g = deserialized_vkey.gamma_2;
-    let vkey_d1 = deserialized_vkey.delta_1;
-    let vkey_d2 = deserialized_vkey.delta_2;
-    let vkey_ic_1 = &deserialized_vkey.ic[0];
-    let vkey_ic_2 = &deserialized_vkey.ic[1];
-
-    let res_va = format!("{}{}", "0x", encode_hex(&vkey_a));
-    let res_vb1 = format!("{}{}", "0x", encode_hex(&vkey_b1));
-    let res_vb2 = format!("{}{}", "0x", encode_hex(&vkey_b2));
-    let res_vg = format!("{}{}", "0x", encode_hex(&vkey_g));
-    let res_vd1 = format!("{}{}", "0x", encode_hex(&vkey_d1));
-    let res_vd2 = format!("{}{}", "0x", encode_hex(&vkey_d2));
-    let res_vic1 = format!("{}{}", "0x", encode_hex(&vkey_ic_1));
-    let res_vic2 = format!("{}{}", "0x", encode_hex(&vkey_ic_2));
-    println!("pi_a: {}", res_a);
-    println!("pi_b: {}", res_b);
-    println!("pi_c: {}", res_c);
-    println!("vkey_alpha: {}", res_va);
-    println!("vkey_beta1: {}", res_vb1);
-    println!("vkey_beta2: {}", res_vb2);
-    println!("vkey_gamma: {}", res_vg);
-    println!("vkey_delta1: {}", res_vd1);
-    println!("vkey_delta2: {}", res_vd2);
-    println!("vkey_ic_1: {}", res_vic1);
-    println!("vkey_ic_2: {}", res_vic2);
-
-
-    let mut file_proofa = File::create("./file/proof_a.txt").unwrap();
-    file_proofa.write(res_a.as_bytes())?;
-    let mut file_proofb = File::create("./file/proof_b.txt").unwrap();
-    file_proofb.write(res_b.as_bytes())?;
-    let mut file_proofc = File::create("./file/proof_c.txt").unwrap();
-    file_proofc.write(res_c.as_bytes())?;
-
-    let mut file_vkey_a = File::create("./file/vkey_a.txt").unwrap();
-    file_vkey_a.write(res_va.as_bytes())?;
-    let mut file_vkey_b1 = File::create("./file/vkey_b1.txt").unwrap();
-    file_vkey_b1.write(res_vb1.as_bytes())?;
-    let mut file_vkey_b2 = File::create("./file/vkey_b2.txt").unwrap();
-    file_vkey_b2.write(res_vb2.as_bytes())?;
-    let mut file_vkey_g = File::create("./file/vkey_g.txt").unwrap();
-    file_vkey_g.write(res_vg.as_bytes())?;
-    let mut file_vkey_d1 = File::create("./file/vkey_d1.txt").unwrap();
-    file_vkey_d1.write(res_vd1.as_bytes())?;
-    let mut file_vkey_d2 = File::create("./file/vkey_d2.txt").unwrap();
-    file_vkey_d2.write(res_vd2.as_bytes())?;
-    let mut file_vkey_ic_1 = File::create("./file/vkey_ic_1.txt").unwrap();
-    file_vkey_ic_1.write(res_vic1.as_bytes())?;
-    let mut file_vkey_ic_2 = File::create("./file/vkey_ic_2.txt").unwrap();
-    file_vkey_ic_2.write(res_vic2.as_bytes())?;
+/// A Groth16 proof with each point as `0x`-prefixed hex of its uncompressed
+/// bytes, the form an on-chain verifier takes.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct HexProof {
+    pub pi_a: String,
+    pub pi_b: String,
+    pub pi_c: String,
+}
+
+/// A Groth16 verifying key with each point as `0x`-prefixed hex, with one
+/// `ic` entry per public input plus one for the constant term.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct HexVerifyKey {
+    pub alpha_1: String,
+    pub beta_1: String,
+    pub beta_2: String,
+    pub gamma_2: String,
+    pub delta_1: String,
+    pub delta_2: String,
+    pub ic: Vec<String>,
+}
+
+fn encode_point(bytes: &[u8]) -> String {
+    format!("{}{}", "0x", encode_hex(bytes))
+}
+
+impl From<&Proof> for HexProof {
+    fn from(proof: &Proof) -> Self {
+        HexProof {
+            pi_a: encode_point(&proof.pi_a),
+            pi_b: encode_point(&proof.pi_b),
+            pi_c: encode_point(&proof.pi_c),
+        }
+    }
+}
+
+impl From<&VerifyKey> for HexVerifyKey {
+    fn from(vkey: &VerifyKey) -> Self {
+        HexVerifyKey {
+            alpha_1: encode_point(&vkey.alpha_1),
+            beta_1: encode_point(&vkey.beta_1),
+            beta_2: encode_point(&vkey.beta_2),
+            gamma_2: encode_point(&vkey.gamma_2),
+            delta_1: encode_point(&vkey.delta_1),
+            delta_2: encode_point(&vkey.delta_2),
+            ic: vkey.ic.iter().map(|p| encode_point(p)).collect(),
+        }
+    }
+}
+
+/// Reads back the uncompressed JSON written by `create_uncompressed_file` and
+/// writes every point as hex, both as `proof_hex.json`/`vkey_hex.json` and as
+/// one `.txt` file per point. IC points go to `vkey_ic_1.txt`, `vkey_ic_2.txt`
+/// and so on, however many the circuit has. Returns the hex points.
+pub fn encode_uncompressed(files: &OutputFiles) -> Result<(HexProof, HexVerifyKey)> {
+    let proof_file = File::open(files.proof_path("proof_uncompressed.json"))?;
+    let vkey_file = File::open(files.vkey_path("vkey_uncompressed.json"))?;
+
+    let deserialized_proof: Proof = serde_json::from_reader(BufReader::new(proof_file))?;
+    let deserialized_vkey: VerifyKey = serde_json::from_reader(BufReader::new(vkey_file))?;
+
+    let hex_proof = HexProof::from(&deserialized_proof);
+    let hex_vkey = HexVerifyKey::from(&deserialized_vkey);
+
+    serde_json::to_writer_pretty(
+        File::create(files.proof_path("proof_hex.json"))?,
+        &hex_proof,
+    )?;
+    serde_json::to_writer_pretty(File::create(files.vkey_path("vkey_hex.json"))?, &hex_vkey)?;
+
+    let proof_points = [
+        ("proof_a.txt", &hex_proof.pi_a),
+        ("proof_b.txt", &hex_proof.pi_b),
+        ("proof_c.txt", &hex_proof.pi_c),
+    ];
+    for (name, hex) in proof_points {
+        File::create(files.proof_path(name))?.write_all(hex.as_bytes())?;
+    }
+
+    let vkey_points = [
+        ("vkey_a.txt", &hex_vkey.alpha_1),
+        ("vkey_b1.txt", &hex_vkey.beta_1),
+        ("vkey_b2.txt", &hex_vkey.beta_2),
+        ("vkey_g.txt", &hex_vkey.gamma_2),
+        ("vkey_d1.txt", &hex_vkey.delta_1),
+        ("vkey_d2.txt", &hex_vkey.delta_2),
+    ];
+    for (name, hex) in vkey_points {
+        File::create(files.vkey_path(name))?.write_all(hex.as_bytes())?;
+    }
+
+    for (i, hex) in hex_vkey.ic.iter().enumerate() {
+        File::create(files.vkey_path(&format!("vkey_ic_{}.txt", i + 1)))?
+            .write_all(hex.as_bytes())?;
+    }
+
+    Ok((hex_proof, hex_vkey))
+}
+
+/// A Groth16 proof in the snarkjs `proof.json` layout.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SnarkjsProof {
+    pub pi_a: Vec<String>,
+    pub pi_b: Vec<Vec<String>>,
+    pub pi_c: Vec<String>,
+    pub protocol: String,
+    pub curve: String,
+}
+
+/// A Groth16 verifying key in the snarkjs `verification_key.json` layout.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SnarkjsVerifyKey {
+    pub protocol: String,
+    pub curve: String,
+    #[serde(rename = "nPublic")]
+    pub n_public: usize,
+    pub vk_alpha_1: Vec<String>,
+    pub vk_beta_2: Vec<Vec<String>>,
+    pub vk_gamma_2: Vec<Vec<String>>,
+    pub vk_delta_2: Vec<Vec<String>>,
+    #[serde(rename = "IC")]
+    pub ic: Vec<Vec<String>>,
+}
+
+pub const SNARKJS_PROTOCOL: &str = "groth16";
+pub const SNARKJS_CURVE: &str = "bls12381";
+
+/// Converts a big-endian base field element to a decimal string, ignoring
+/// the flag bits that share the first byte of an encoded point.
+fn fp_to_decimal(bytes: &[u8]) -> String {
+    let mut bytes = bytes.to_vec();
+    bytes[0] &= 0x1f;
+    BigUint::from_bytes_be(&bytes).to_str_radix(10)
+}
+
+/// Bit set in the first byte of an encoded point at infinity.
+const INFINITY_FLAG: u8 = 0x40;
+
+/// Projective `[x, y, z]` coordinates of an uncompressed G1 point.
+fn g1_to_snarkjs(name: &str, bytes: &[u8]) -> Result<Vec<String>> {
+    if bytes.len() != 96 {
+        return Err(Error::InvalidFormat(format!(
+            "{}: expected 96 bytes, found {}",
+            name,
+            bytes.len()
+        )));
+    }
+    if bytes[0] & INFINITY_FLAG != 0 {
+        return Ok(vec!["0".to_string(), "1".to_string(), "0".to_string()]);
+    }
+
+    Ok(vec![
+        fp_to_decimal(&bytes[..48]),
+        fp_to_decimal(&bytes[48..]),
+        "1".to_string(),
+    ])
+}
+
+/// Projective `[[x.c0, x.c1], [y.c0, y.c1], [z.c0, z.c1]]` coordinates of an
+/// uncompressed G2 point. The byte encoding stores `c1` before `c0`.
+fn g2_to_snarkjs(name: &str, bytes: &[u8]) -> Result<Vec<Vec<String>>> {
+    if bytes.len() != 192 {
+        return Err(Error::InvalidFormat(format!(
+            "{}: expected 192 bytes, found {}",
+            name,
+            bytes.len()
+        )));
+    }
+    let pair = |c0: &str, c1: &str| vec![c0.to_string(), c1.to_string()];
+    if bytes[0] & INFINITY_FLAG != 0 {
+        return Ok(vec![pair("0", "0"), pair("1", "0"), pair("0", "0")]);
+    }
+
+    Ok(vec![
+        vec![fp_to_decimal(&bytes[48..96]), fp_to_decimal(&bytes[..48])],
+        vec![fp_to_decimal(&bytes[144..]), fp_to_decimal(&bytes[96..144])],
+        pair("1", "0"),
+    ])
+}
+
+/// Decimal string of a scalar, as used in snarkjs `public.json`.
+pub fn scalar_to_decimal(s: &Scalar) -> String {
+    BigUint::from_bytes_le(s.to_repr().as_ref()).to_str_radix(10)
+}
+
+impl TryFrom<&Proof> for SnarkjsProof {
+    type Error = Error;
+
+    fn try_from(proof: &Proof) -> Result<Self> {
+        Ok(SnarkjsProof {
+            pi_a: g1_to_snarkjs("pi_a", &proof.pi_a)?,
+            pi_b: g2_to_snarkjs("pi_b", &proof.pi_b)?,
+            pi_c: g1_to_snarkjs("pi_c", &proof.pi_c)?,
+            protocol: SNARKJS_PROTOCOL.to_string(),
+            curve: SNARKJS_CURVE.to_string(),
+        })
+    }
+}
+
+impl TryFrom<&VerifyKey> for SnarkjsVerifyKey {
+    type Error = Error;
+
+    fn try_from(vkey: &VerifyKey) -> Result<Self> {
+        if vkey.ic.is_empty() {
+            return Err(Error::InvalidFormat(
+                "ic: expected at least one point".to_string(),
+            ));
+        }
+
+        Ok(SnarkjsVerifyKey {
+            protocol: SNARKJS_PROTOCOL.to_string(),
+            curve: SNARKJS_CURVE.to_string(),
+            n_public: vkey.ic.len() - 1,
+            vk_alpha_1: g1_to_snarkjs("alpha_1", &vkey.alpha_1)?,
+            vk_beta_2: g2_to_snarkjs("beta_2", &vkey.beta_2)?,
+            vk_gamma_2: g2_to_snarkjs("gamma_2", &vkey.gamma_2)?,
+            vk_delta_2: g2_to_snarkjs("delta_2", &vkey.delta_2)?,
+            ic: vkey
+                .ic
+                .iter()
+                .enumerate()
+                .map(|(i, p)| g1_to_snarkjs(&format!("ic[{}]", i), p))
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+/// Writes a proof as snarkjs `proof.json`.
+pub fn write_snarkjs_proof<W: Write>(proof: &Proof, writer: W) -> Result<()> {
+    serde_json::to_writer_pretty(writer, &SnarkjsProof::try_from(proof)?)?;
+    Ok(())
+}
+
+pub fn write_snarkjs_vkey<W: Write>(vkey: &VerifyKey, writer: W) -> Result<()> {
+    serde_json::to_writer_pretty(writer, &SnarkjsVerifyKey::try_from(vkey)?)?;
+    Ok(())
+}
+
+pub fn write_snarkjs_public<W: Write>(public: &[Scalar], writer: W) -> Result<()> {
+    let public: Vec<String> = public.iter().map(scalar_to_decimal).collect();
+    serde_json::to_writer_pretty(writer, &public)?;
+    Ok(())
+}
+
+/// Writes `proof.json`, `verification_key.json` and `public.json` in the
+/// snarkjs layout.
+pub fn create_snarkjs_files(
+    files: &OutputFiles,
+    proof: &Proof,
+    vkey: &VerifyKey,
+    public: &[Scalar],
+) -> Result<()> {
+    let file_proof = File::create(files.proof_path("proof.json"))?;
+    write_snarkjs_proof(proof, file_proof)?;
+
+    let file_vkey = File::create(files.vkey_path("verification_key.json"))?;
+    write_snarkjs_vkey(vkey, file_vkey)?;
+
+    let file_public = File::create(files.proof_path("public.json"))?;
+    write_snarkjs_public(public, file_public)?;
+
+    Ok(())
+}
+
+/// Parses a decimal base field element into 48 big-endian bytes. Values that
+/// are not below the field modulus are rejected when the point is decoded.
+fn fp_from_decimal(name: &str, s: &str) -> Result<[u8; 48]> {
+    let n = BigUint::parse_bytes(s.as_bytes(), 10)
+        .ok_or_else(|| Error::InvalidFormat(format!("{}: invalid decimal `{}`", name, s)))?;
+    let bytes = n.to_bytes_be();
+    if bytes.len() > 48 {
+        return Err(Error::InvalidFormat(format!(
+            "{}: coordinate out of range",
+            name
+        )));
+    }
+
+    let mut out = [0u8; 48];
+    out[48 - bytes.len()..].copy_from_slice(&bytes);
+    Ok(out)
+}
+
+/// Uncompressed bytes of a G1 point given as snarkjs `[x, y, z]`, where `z`
+/// is `1` for an affine point or `0` for the point at infinity.
+fn g1_from_snarkjs(name: &str, coords: &[String]) -> Result<Vec<u8>> {
+    if coords.len() != 3 {
+        return Err(Error::InvalidFormat(format!(
+            "{}: expected 3 coordinates",
+            name
+        )));
+    }
+
+    let mut out = vec![0u8; 96];
+    match coords[2].as_str() {
+        "0" => out[0] = INFINITY_FLAG,
+        "1" => {
+            out[..48].copy_from_slice(&fp_from_decimal(name, &coords[0])?);
+            out[48..].copy_from_slice(&fp_from_decimal(name, &coords[1])?);
+        }
+        _ => {
+            return Err(Error::InvalidFormat(format!(
+                "{}: point is not normalized",
+                name
+            )))
+        }
+    }
+    Ok(out)
+}
+
+/// Uncompressed bytes of a G2 point given as snarkjs
+/// `[[x.c0, x.c1], [y.c0, y.c1], [z.c0, z.c1]]`.
+fn g2_from_snarkjs(name: &str, coords: &[Vec<String>]) -> Result<Vec<u8>> {
+    if coords.len() != 3 || coords.iter().any(|c| c.len() != 2) {
+        return Err(Error::InvalidFormat(format!(
+            "{}: expected 3 coordinate pairs",
+            name
+        )));
+    }
+
+    let mut out = vec![0u8; 192];
+    match (coords[2][0].as_str(), coords[2][1].as_str()) {
+        ("0", "0") => out[0] = INFINITY_FLAG,
+        ("1", "0") => {
+            out[..48].copy_from_slice(&fp_from_decimal(name, &coords[0][1])?);
+            out[48..96].copy_from_slice(&fp_from_decimal(name, &coords[0][0])?);
+            out[96..144].copy_from_slice(&fp_from_decimal(name, &coords[1][1])?);
+            out[144..].copy_from_slice(&fp_from_decimal(name, &coords[1][0])?);
+        }
+        _ => {
+            return Err(Error::InvalidFormat(format!(
+                "{}: point is not normalized",
+                name
+            )))
+        }
+    }
+    Ok(out)
+}
+
+/// Parses a decimal scalar, rejecting values that are not below the group order.
+pub fn scalar_from_decimal(s: &str) -> Result<Scalar> {
+    scalar_from_str_radix(s, 10)
+}
+
+/// Parses a scalar in the given radix, rejecting values that are not below
+/// the group order. Only the digits of `radix` are accepted, so no sign or
+/// `_` separators.
+pub fn scalar_from_str_radix(s: &str, radix: u32) -> Result<Scalar> {
+    if s.is_empty() || !s.chars().all(|c| c.is_digit(radix)) {
+        return Err(Error::InvalidScalar(s.to_string()));
+    }
+    let bytes = BigUint::parse_bytes(s.as_bytes(), radix)
+        .ok_or_else(|| Error::InvalidScalar(s.to_string()))?
+        .to_bytes_le();
+    if bytes.len() > 32 {
+        return Err(Error::InvalidScalar(s.to_string()));
+    }
+
+    let mut repr = [0u8; 32];
+    repr[..bytes.len()].copy_from_slice(&bytes);
+    Option::from(Scalar::from_repr(repr)).ok_or_else(|| Error::InvalidScalar(s.to_string()))
+}
+
+fn check_snarkjs_header(protocol: &str, curve: &str) -> Result<()> {
+    if protocol != SNARKJS_PROTOCOL {
+        return Err(Error::InvalidFormat(format!(
+            "unsupported protocol `{}`",
+            protocol
+        )));
+    }
+    if curve != SNARKJS_CURVE {
+        return Err(Error::InvalidFormat(format!(
+            "unsupported curve `{}`",
+            curve
+        )));
+    }
+    Ok(())
+}
+
+impl SnarkjsProof {
+    pub fn to_groth16(&self) -> Result<groth16::Proof<Bls12>> {
+        check_snarkjs_header(&self.protocol, &self.curve)?;
+
+        Proof {
+            pi_a: g1_from_snarkjs("pi_a", &self.pi_a)?,
+            pi_b: g2_from_snarkjs("pi_b", &self.pi_b)?,
+            pi_c: g1_from_snarkjs("pi_c", &self.pi_c)?,
+        }
+        .to_groth16()
+    }
+}
+
+impl SnarkjsVerifyKey {
+    /// snarkjs keys carry no `beta_1` or `delta_1`. Verification never reads
+    /// them, so the key is returned already prepared.
+    pub fn to_prepared(&self) -> Result<groth16::PreparedVerifyingKey<Bls12>> {
+        check_snarkjs_header(&self.protocol, &self.curve)?;
+        let expected = self.n_public.checked_add(1).ok_or_else(|| {
+            Error::InvalidFormat(format!("nPublic: {} is out of range", self.n_public))
+        })?;
+        if self.ic.len() != expected {
+            return Err(Error::InvalidFormat(format!(
+                "IC: expected {} points for {} public inputs, found {}",
+                expected,
+                self.n_public,
+                self.ic.len()
+            )));
+        }
+
+        let unused = G1Affine::identity().to_uncompressed().to_vec();
+        let vkey = VerifyKey {
+            alpha_1: g1_from_snarkjs("vk_alpha_1", &self.vk_alpha_1)?,
+            beta_1: unused.clone(),
+            beta_2: g2_from_snarkjs("vk_beta_2", &self.vk_beta_2)?,
+            gamma_2: g2_from_snarkjs("vk_gamma_2", &self.vk_gamma_2)?,
+            delta_1: unused,
+            delta_2: g2_from_snarkjs("vk_delta_2", &self.vk_delta_2)?,
+            ic: self
+                .ic
+                .iter()
+                .enumerate()
+                .map(|(i, p)| g1_from_snarkjs(&format!("IC[{}]", i), p))
+                .collect::<Result<_>>()?,
+        };
+        Ok(groth16::prepare_verifying_key(&vkey.to_groth16()?))
+    }
+}
+
+/// Reads a snarkjs `proof.json`.
+pub fn read_snarkjs_proof<R: Read>(reader: R) -> Result<groth16::Proof<Bls12>> {
+    let proof: SnarkjsProof = serde_json::from_reader(reader)?;
+    proof.to_groth16()
+}
+
+/// Reads a snarkjs `verification_key.json`.
+pub fn read_snarkjs_vkey<R: Read>(reader: R) -> Result<groth16::PreparedVerifyingKey<Bls12>> {
+    let vkey: SnarkjsVerifyKey = serde_json::from_reader(reader)?;
+    vkey.to_prepared()
+}
+
+/// Reads a snarkjs `public.json`.
+pub fn read_snarkjs_public<R: Read>(reader: R) -> Result<Vec<Scalar>> {
+    let public: Vec<String> = serde_json::from_reader(reader)?;
+    public.iter().map(|s| scalar_from_decimal(s)).collect()
+}
+
+pub fn load_snarkjs_proof<P: AsRef<Path>>(path: P) -> Result<groth16::Proof<Bls12>> {
+    read_snarkjs_proof(BufReader::new(File::open(path)?))
+}
+
+pub fn load_snarkjs_vkey<P: AsRef<Path>>(path: P) -> Result<groth16::PreparedVerifyingKey<Bls12>> {
+    read_snarkjs_vkey(BufReader::new(File::open(path)?))
+}
+
+pub fn load_snarkjs_public<P: AsRef<Path>>(path: P) -> Result<Vec<Scalar>> {
+    read_snarkjs_public(BufReader::new(File::open(path)?))
+}
+
+/// Writes every export of `proof` and `vk` into `files.dir`: the
+/// uncompressed JSON and hex, the snarkjs files, a Solidity verifier and
+/// its calldata.
+pub fn export(
+    vk: &groth16::VerifyingKey<Bls12>,
+    proof: &groth16::Proof<Bls12>,
+    inputs: &[Scalar],
+    files: &OutputFiles,
+) -> Result<()> {
+    let uncompressed_proof = Proof::from(proof);
+    let vkey = VerifyKey::from(vk);
+
+    std::fs::create_dir_all(&files.dir)?;
+    create_uncompressed_file(files, &uncompressed_proof, &vkey)?;
+    encode_uncompressed(files)?;
+    create_snarkjs_files(files, &uncompressed_proof, &vkey, inputs)?;
+    solidity::create_verifier_file(
+        files.vkey_path("Verifier.sol"),
+        vk,
+        &solidity::contract_name(&files.circuit)?,
+    )?;
+    solidity::create_calldata_file(files.proof_path("calldata.txt"), proof, inputs)?;
 
     Ok(())
+}
+
+#[test]
+fn test_uncompressed_round_trip() {
+    use bellman::groth16::{prepare_verifying_key, verify_proof};
+
+    use crate::cube;
+    use crate::rng::seeded;
+
+    let (params, proof, public) = cube::test_proof(&mut seeded(0));
 
+    let res_proof = serde_json::to_string(&Proof::from(&proof)).unwrap();
+    let res_vkey = serde_json::to_string(&VerifyKey::from(&params.vk)).unwrap();
+
+    let loaded_proof = read_uncompressed_proof(res_proof.as_bytes()).unwrap();
+    let loaded_vkey = read_uncompressed_vkey(res_vkey.as_bytes()).unwrap();
+    assert_eq!(loaded_proof, proof);
+
+    assert!(verify_proof(&prepare_verifying_key(&loaded_vkey), &loaded_proof, &public).is_ok());
+
+    // A point that is not on the curve is rejected.
+    let mut bad_proof = Proof::from(&proof);
+    bad_proof.pi_a[95] ^= 1;
+    assert!(bad_proof.to_groth16().is_err());
+}
+
+#[test]
+fn test_snarkjs_generators() {
+    let g1 = G1Affine::generator().to_uncompressed();
+    assert_eq!(
+        g1_to_snarkjs("g1", &g1).unwrap(),
+        vec![
+            "3685416753713387016781088315183077757961620795782546409894578378688607592378376318836054947676345821548104185464507",
+            "1339506544944476473020471379941921221584933875938349620426543736416511423956333506472724655353366534992391756441569",
+            "1",
+        ]
+    );
+
+    let g2 = G2Affine::generator().to_uncompressed();
+    assert_eq!(
+        g2_to_snarkjs("g2", &g2).unwrap(),
+        vec![
+            vec![
+                "352701069587466618187139116011060144890029952792775240219908644239793785735715026873347600343865175952761926303160",
+                "3059144344244213709971259814753781636986470325476647558659373206291635324768958432433509563104347017837885763365758",
+            ],
+            vec![
+                "1985150602287291935568054521177171638300868978215655730859378665066344726373823718423869104263333984641494340347905",
+                "927553665492332455747201965776037880757740193453592970025027978793976877002675564980949289727957565575433344219582",
+            ],
+            vec!["1", "0"],
+        ]
+    );
+
+    let inf = G2Affine::identity().to_uncompressed();
+    assert_eq!(g2_to_snarkjs("inf", &inf).unwrap()[2], vec!["0", "0"]);
+
+    assert_eq!(scalar_to_decimal(&Scalar::from(35u64)), "35");
+}
+
+#[test]
+fn test_snarkjs_round_trip() {
+    use bellman::groth16::verify_proof;
+
+    use crate::cube;
+    use crate::rng::seeded;
+
+    let (params, proof, _) = cube::test_proof(&mut seeded(0));
+
+    let snarkjs_proof = SnarkjsProof::try_from(&Proof::from(&proof)).unwrap();
+    let snarkjs_vkey = SnarkjsVerifyKey::try_from(&VerifyKey::from(&params.vk)).unwrap();
+    let proof_json = serde_json::to_string(&snarkjs_proof).unwrap();
+    let vkey_json = serde_json::to_string(&snarkjs_vkey).unwrap();
+
+    let loaded_proof = read_snarkjs_proof(proof_json.as_bytes()).unwrap();
+    let pvk = read_snarkjs_vkey(vkey_json.as_bytes()).unwrap();
+    let public = read_snarkjs_public(&br#"["35"]"#[..]).unwrap();
+    assert_eq!(loaded_proof, proof);
+
+    assert!(verify_proof(&pvk, &loaded_proof, &public).is_ok());
+    assert!(verify_proof(&pvk, &loaded_proof, &[Scalar::from(36u64)]).is_err());
+
+    // A public input count that does not match the IC points, or that would
+    // overflow, is rejected rather than trusted.
+    let mut bad_vkey = snarkjs_vkey.clone();
+    bad_vkey.n_public = usize::MAX;
+    assert!(matches!(
+        bad_vkey.to_prepared(),
+        Err(Error::InvalidFormat(_))
+    ));
+    bad_vkey.n_public = 2;
+    assert!(matches!(
+        bad_vkey.to_prepared(),
+        Err(Error::InvalidFormat(_))
+    ));
+
+    // Other curves and out-of-range scalars are rejected.
+    let mut bn128 = snarkjs_proof;
+    bn128.curve = "bn128".to_string();
+    assert!(bn128.to_groth16().is_err());
+    assert!(scalar_from_decimal(&scalar_to_decimal(&-Scalar::one())).is_ok());
+    assert!(scalar_from_decimal(
+        "52435875175126190479447740508185965837690552500527637822603658699938581184513"
+    )
+    .is_err());
+}
+
+#[test]
+fn test_hex_export_any_ic_count() {
+    let g1 = G1Affine::generator().to_uncompressed().to_vec();
+    let g2 = G2Affine::generator().to_uncompressed().to_vec();
+    let proof = Proof {
+        pi_a: g1.clone(),
+        pi_b: g2.clone(),
+        pi_c: g1.clone(),
+    };
+
+    for n_ic in [1, 3] {
+        let vkey = VerifyKey {
+            alpha_1: g1.clone(),
+            beta_1: g1.clone(),
+            beta_2: g2.clone(),
+            gamma_2: g2.clone(),
+            delta_1: g1.clone(),
+            delta_2: g2.clone(),
+            ic: vec![g1.clone(); n_ic],
+        };
+
+        let dir = tempfile::tempdir().unwrap();
+        let files = OutputFiles::new(dir.path(), "test", "0");
+        create_uncompressed_file(&files, &proof, &vkey).unwrap();
+        let (_, hex_vkey) = encode_uncompressed(&files).unwrap();
+
+        let written: HexVerifyKey =
+            serde_json::from_reader(File::open(files.vkey_path("vkey_hex.json")).unwrap()).unwrap();
+        assert_eq!(written, hex_vkey);
+        assert_eq!(hex_vkey.ic.len(), n_ic);
+        assert_eq!(hex_vkey.ic[0], encode_point(&g1));
+        assert!(files.vkey_path(&format!("vkey_ic_{}.txt", n_ic)).exists());
+        assert!(!files
+            .vkey_path(&format!("vkey_ic_{}.txt", n_ic + 1))
+            .exists());
+    }
 }
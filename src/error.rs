@@ -0,0 +1,93 @@
+use std::fmt;
+use std::io;
+
+use bellman::{SynthesisError, VerificationError};
+
+/// Everything that can go wrong while setting up, proving, verifying or
+/// exporting.
+#[derive(Debug)]
+pub enum Error {
+    /// Reading or writing a file failed.
+    Io(io::Error),
+    /// A JSON file could not be parsed or written.
+    Json(serde_json::Error),
+    /// A file has the wrong layout, length or version.
+    InvalidFormat(String),
+    /// Bytes that do not decode to a point in the prime-order subgroup.
+    InvalidPoint(String),
+    /// A string that is not a canonical field element.
+    InvalidScalar(String),
+    /// An arithmetic expression that does not parse.
+    InvalidExpression(String),
+    /// A Merkle tree that cannot be built, e.g. with too many leaves.
+    InvalidTree(String),
+    /// A witness value the circuit needs was not supplied.
+    MissingWitness(String),
+    /// A supplied public input disagrees with the one the witness implies.
+    InconsistentInput(String),
+    /// No circuit is known under this name.
+    UnknownCircuit(String),
+    /// Synthesizing the circuit failed.
+    Synthesis(SynthesisError),
+    /// The proof does not verify against the key and public inputs.
+    Verification(VerificationError),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "I/O error: {}", e),
+            Error::Json(e) => write!(f, "JSON error: {}", e),
+            Error::InvalidFormat(msg) => write!(f, "invalid format: {}", msg),
+            Error::InvalidPoint(name) => write!(f, "{}: not a valid curve point", name),
+            Error::InvalidScalar(s) => write!(f, "invalid field element `{}`", s),
+            Error::InvalidExpression(msg) => write!(f, "invalid expression: {}", msg),
+            Error::InvalidTree(msg) => write!(f, "invalid Merkle tree: {}", msg),
+            Error::MissingWitness(name) => write!(f, "missing witness value `{}`", name),
+            Error::InconsistentInput(name) => {
+                write!(f, "`{}` does not match the value implied by the witness", name)
+            }
+            Error::UnknownCircuit(name) => write!(f, "unknown circuit `{}`", name),
+            Error::Synthesis(e) => write!(f, "synthesis error: {}", e),
+            Error::Verification(e) => write!(f, "verification failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Json(e) => Some(e),
+            Error::Synthesis(e) => Some(e),
+            Error::Verification(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Json(e)
+    }
+}
+
+impl From<SynthesisError> for Error {
+    fn from(e: SynthesisError) -> Self {
+        Error::Synthesis(e)
+    }
+}
+
+impl From<VerificationError> for Error {
+    fn from(e: VerificationError) -> Self {
+        Error::Verification(e)
+    }
+}
@@ -0,0 +1,592 @@
+use std::collections::HashMap;
+
+use ff::PrimeField;
+
+use bellman::{Circuit, ConstraintSystem, LinearCombination, SynthesisError, Variable};
+
+use crate::error::{Error, Result};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Num(String),
+    Ident(String),
+    Plus,
+    Minus,
+    Star,
+    Caret,
+    LParen,
+    RParen,
+    EqEq,
+}
+
+fn invalid(msg: impl Into<String>) -> Error {
+    Error::InvalidExpression(msg.into())
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token>> {
+    let mut tokens = vec![];
+    let mut chars = src.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_alphanumeric() || c == '_' {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if !(c.is_ascii_alphanumeric() || c == '_') {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            if word.starts_with(|c: char| c.is_ascii_digit()) {
+                if !word.chars().all(|c| c.is_ascii_digit()) {
+                    return Err(invalid(format!("bad number `{}`", word)));
+                }
+                tokens.push(Token::Num(word));
+            } else {
+                tokens.push(Token::Ident(word));
+            }
+        } else {
+            chars.next();
+            tokens.push(match c {
+                '+' => Token::Plus,
+                '-' => Token::Minus,
+                '*' => Token::Star,
+                '^' => Token::Caret,
+                '(' => Token::LParen,
+                ')' => Token::RParen,
+                '=' if chars.next() == Some('=') => Token::EqEq,
+                _ => return Err(invalid(format!("unexpected character `{}`", c))),
+            });
+        }
+    }
+
+    Ok(tokens)
+}
+
+#[derive(Clone, Debug)]
+enum Expr<S: PrimeField> {
+    Const(S),
+    Var(String),
+    Neg(Box<Expr<S>>),
+    Add(Box<Expr<S>>, Box<Expr<S>>),
+    Sub(Box<Expr<S>>, Box<Expr<S>>),
+    Mul(Box<Expr<S>>, Box<Expr<S>>),
+    Pow(Box<Expr<S>>, u32),
+}
+
+impl<S: PrimeField> Expr<S> {
+    fn eval(&self, values: &HashMap<String, S>) -> Result<S> {
+        Ok(match self {
+            Expr::Const(c) => *c,
+            Expr::Var(name) => *values
+                .get(name)
+                .ok_or_else(|| Error::MissingWitness(name.clone()))?,
+            Expr::Neg(a) => -a.eval(values)?,
+            Expr::Add(a, b) => a.eval(values)? + b.eval(values)?,
+            Expr::Sub(a, b) => a.eval(values)? - b.eval(values)?,
+            Expr::Mul(a, b) => a.eval(values)? * b.eval(values)?,
+            Expr::Pow(a, n) => a.eval(values)?.pow_vartime([u64::from(*n)]),
+        })
+    }
+
+    /// Pushes every variable not yet in `names`, in order of appearance.
+    fn variables(&self, names: &mut Vec<String>) {
+        match self {
+            Expr::Const(_) => {}
+            Expr::Var(name) => {
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+            Expr::Neg(a) | Expr::Pow(a, _) => a.variables(names),
+            Expr::Add(a, b) | Expr::Sub(a, b) | Expr::Mul(a, b) => {
+                a.variables(names);
+                b.variables(names);
+            }
+        }
+    }
+}
+
+/// Recursive descent over
+///
+/// ```text
+/// sum   = term (("+" | "-") term)*
+/// term  = unary ("*" unary)*
+/// unary = "-" unary | power
+/// power = atom ("^" number)?
+/// atom  = number | name | "(" sum ")"
+/// ```
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<&'a Token> {
+        let token = self.tokens.get(self.pos);
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn sum<S: PrimeField>(&mut self) -> Result<Expr<S>> {
+        let mut expr = self.term()?;
+        loop {
+            if self.eat(&Token::Plus) {
+                expr = Expr::Add(Box::new(expr), Box::new(self.term()?));
+            } else if self.eat(&Token::Minus) {
+                expr = Expr::Sub(Box::new(expr), Box::new(self.term()?));
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn term<S: PrimeField>(&mut self) -> Result<Expr<S>> {
+        let mut expr = self.unary()?;
+        while self.eat(&Token::Star) {
+            expr = Expr::Mul(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary<S: PrimeField>(&mut self) -> Result<Expr<S>> {
+        if self.eat(&Token::Minus) {
+            Ok(Expr::Neg(Box::new(self.unary()?)))
+        } else {
+            self.power()
+        }
+    }
+
+    fn power<S: PrimeField>(&mut self) -> Result<Expr<S>> {
+        let atom = self.atom()?;
+        if !self.eat(&Token::Caret) {
+            return Ok(atom);
+        }
+        match self.next() {
+            Some(Token::Num(n)) => {
+                let n = n
+                    .parse()
+                    .map_err(|_| invalid(format!("exponent `{}` is too large", n)))?;
+                Ok(Expr::Pow(Box::new(atom), n))
+            }
+            _ => Err(invalid("exponents must be integer literals")),
+        }
+    }
+
+    fn atom<S: PrimeField>(&mut self) -> Result<Expr<S>> {
+        match self.next() {
+            Some(Token::Num(n)) => S::from_str_vartime(n)
+                .map(Expr::Const)
+                .ok_or_else(|| Error::InvalidScalar(n.clone())),
+            Some(Token::Ident(name)) => Ok(Expr::Var(name.clone())),
+            Some(Token::LParen) => {
+                let expr = self.sum()?;
+                if !self.eat(&Token::RParen) {
+                    return Err(invalid("missing `)`"));
+                }
+                Ok(expr)
+            }
+            Some(token) => Err(invalid(format!("unexpected {:?}", token))),
+            None => Err(invalid("unexpected end of expression")),
+        }
+    }
+}
+
+/// An equation `lhs == rhs` over named field elements, some of them public.
+#[derive(Clone, Debug)]
+pub struct Expression<S: PrimeField> {
+    lhs: Expr<S>,
+    rhs: Expr<S>,
+    public: Vec<String>,
+    private: Vec<String>,
+}
+
+impl<S: PrimeField> Expression<S> {
+    /// Parses an equation such as `x*y + z^2 - 3 == out`. The variables
+    /// named in `public` become public inputs in that order; all others
+    /// are private.
+    pub fn parse(src: &str, public: &[&str]) -> Result<Self> {
+        let tokens = tokenize(src)?;
+        let mut sides = tokens.split(|t| *t == Token::EqEq);
+        let (lhs, rhs) = match (sides.next(), sides.next(), sides.next()) {
+            (Some(lhs), Some(rhs), None) => (lhs, rhs),
+            _ => return Err(invalid(format!("`{}` needs exactly one `==`", src))),
+        };
+
+        let parse_side = |tokens: &[Token]| -> Result<Expr<S>> {
+            let mut parser = Parser { tokens, pos: 0 };
+            let expr = parser.sum()?;
+            match parser.peek() {
+                Some(token) => Err(invalid(format!("unexpected {:?}", token))),
+                None => Ok(expr),
+            }
+        };
+        let (lhs, rhs) = (parse_side(lhs)?, parse_side(rhs)?);
+
+        let mut names = vec![];
+        lhs.variables(&mut names);
+        rhs.variables(&mut names);
+
+        let mut public_names: Vec<String> = vec![];
+        for name in public {
+            if !names.iter().any(|n| n == name) {
+                return Err(invalid(format!("`{}` does not appear in `{}`", name, src)));
+            }
+            if public_names.iter().any(|n| n == name) {
+                return Err(invalid(format!("`{}` is public twice", name)));
+            }
+            public_names.push(name.to_string());
+        }
+        let private = names
+            .into_iter()
+            .filter(|n| !public_names.contains(n))
+            .collect();
+
+        Ok(Expression {
+            lhs,
+            rhs,
+            public: public_names,
+            private,
+        })
+    }
+
+    pub fn public(&self) -> &[String] {
+        &self.public
+    }
+
+    pub fn private(&self) -> &[String] {
+        &self.private
+    }
+
+    /// The public inputs a verifier needs, in order.
+    pub fn public_inputs(&self, values: &HashMap<String, S>) -> Result<Vec<S>> {
+        self.public
+            .iter()
+            .map(|name| {
+                values
+                    .get(name)
+                    .copied()
+                    .ok_or_else(|| Error::MissingWitness(name.clone()))
+            })
+            .collect()
+    }
+
+    /// Whether the equation holds for `values`, evaluated natively.
+    pub fn is_satisfied(&self, values: &HashMap<String, S>) -> Result<bool> {
+        Ok(self.lhs.eval(values)? == self.rhs.eval(values)?)
+    }
+}
+
+/// A value inside the circuit: either a known constant or a linear
+/// combination of variables with its assignment.
+#[derive(Clone)]
+enum Value<S: PrimeField> {
+    Const(S),
+    Lin(LinearCombination<S>, Option<S>),
+}
+
+impl<S: PrimeField> Value<S> {
+    fn into_lin(self, one: Variable) -> (LinearCombination<S>, Option<S>) {
+        match self {
+            Value::Const(c) => (LinearCombination::zero() + (c, one), Some(c)),
+            Value::Lin(lc, value) => (lc, value),
+        }
+    }
+
+    fn scale(self, c: S) -> Self {
+        match self {
+            Value::Const(a) => Value::Const(a * c),
+            Value::Lin(lc, value) => {
+                Value::Lin(LinearCombination::zero() + (c, &lc), value.map(|v| v * c))
+            }
+        }
+    }
+}
+
+/// Flattens an `Expr` into constraints. Sums and products with constants
+/// are folded into linear combinations; only a product of two non-constant
+/// values allocates a variable and costs a constraint.
+struct Compiler<S: PrimeField> {
+    vars: HashMap<String, (Variable, Option<S>)>,
+    products: usize,
+}
+
+impl<S: PrimeField> Compiler<S> {
+    fn compile<CS: ConstraintSystem<S>>(
+        &mut self,
+        cs: &mut CS,
+        expr: &Expr<S>,
+    ) -> std::result::Result<Value<S>, SynthesisError> {
+        Ok(match expr {
+            Expr::Const(c) => Value::Const(*c),
+            Expr::Var(name) => {
+                let (var, value) = self.vars[name];
+                Value::Lin(LinearCombination::zero() + var, value)
+            }
+            Expr::Neg(a) => self.compile(cs, a)?.scale(-S::one()),
+            Expr::Add(a, b) => {
+                let (a, b) = (self.compile(cs, a)?, self.compile(cs, b)?);
+                self.add::<CS>(a, b)
+            }
+            Expr::Sub(a, b) => {
+                let (a, b) = (self.compile(cs, a)?, self.compile(cs, b)?);
+                self.add::<CS>(a, b.scale(-S::one()))
+            }
+            Expr::Mul(a, b) => {
+                let (a, b) = (self.compile(cs, a)?, self.compile(cs, b)?);
+                self.mul(cs, a, b)?
+            }
+            Expr::Pow(a, n) => {
+                let base = self.compile(cs, a)?;
+                self.pow(cs, base, *n)?
+            }
+        })
+    }
+
+    fn add<CS: ConstraintSystem<S>>(&self, a: Value<S>, b: Value<S>) -> Value<S> {
+        match (a, b) {
+            (Value::Const(a), Value::Const(b)) => Value::Const(a + b),
+            (a, b) => {
+                let (a_lc, a_val) = a.into_lin(CS::one());
+                let (b_lc, b_val) = b.into_lin(CS::one());
+                Value::Lin(a_lc + &b_lc, a_val.zip(b_val).map(|(a, b)| a + b))
+            }
+        }
+    }
+
+    fn mul<CS: ConstraintSystem<S>>(
+        &mut self,
+        cs: &mut CS,
+        a: Value<S>,
+        b: Value<S>,
+    ) -> std::result::Result<Value<S>, SynthesisError> {
+        match (a, b) {
+            (Value::Const(c), v) | (v, Value::Const(c)) => Ok(v.scale(c)),
+            (Value::Lin(a_lc, a_val), Value::Lin(b_lc, b_val)) => {
+                let i = self.products;
+                self.products += 1;
+
+                let value = a_val.zip(b_val).map(|(a, b)| a * b);
+                let product = cs.alloc(
+                    || format!("product {}", i),
+                    || value.ok_or(SynthesisError::AssignmentMissing),
+                )?;
+                cs.enforce(
+                    || format!("product {} = a * b", i),
+                    |lc| lc + &a_lc,
+                    |lc| lc + &b_lc,
+                    |lc| lc + product,
+                );
+
+                Ok(Value::Lin(LinearCombination::zero() + product, value))
+            }
+        }
+    }
+
+    /// Square and multiply, so `x^n` costs about `2 log n` constraints.
+    fn pow<CS: ConstraintSystem<S>>(
+        &mut self,
+        cs: &mut CS,
+        base: Value<S>,
+        mut n: u32,
+    ) -> std::result::Result<Value<S>, SynthesisError> {
+        let mut result = Value::Const(S::one());
+        let mut base = base;
+        while n > 0 {
+            if n & 1 == 1 {
+                result = self.mul(cs, result, base.clone())?;
+            }
+            n >>= 1;
+            if n > 0 {
+                base = self.mul(cs, base.clone(), base)?;
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// Proves that the equation of `expression` holds for the private
+/// variables, given the public ones. `values` may be empty during setup.
+pub struct ExpressionCircuit<'a, S: PrimeField> {
+    pub expression: &'a Expression<S>,
+    pub values: HashMap<String, S>,
+}
+
+impl<'a, S: PrimeField> Circuit<S> for ExpressionCircuit<'a, S> {
+    fn synthesize<CS: ConstraintSystem<S>>(
+        self,
+        cs: &mut CS,
+    ) -> std::result::Result<(), SynthesisError> {
+        let mut vars = HashMap::new();
+
+        // Public inputs first, in their declared order
+        for name in &self.expression.public {
+            let value = self.values.get(name).copied();
+            let var = cs.alloc_input(
+                || name.as_str(),
+                || value.ok_or(SynthesisError::AssignmentMissing),
+            )?;
+            vars.insert(name.clone(), (var, value));
+        }
+        for name in &self.expression.private {
+            let value = self.values.get(name).copied();
+            let var = cs.alloc(
+                || name.as_str(),
+                || value.ok_or(SynthesisError::AssignmentMissing),
+            )?;
+            vars.insert(name.clone(), (var, value));
+        }
+
+        let mut compiler = Compiler { vars, products: 0 };
+        let lhs = compiler.compile(cs, &self.expression.lhs)?;
+        let rhs = compiler.compile(cs, &self.expression.rhs)?;
+        let (diff, _) = compiler
+            .add::<CS>(lhs, rhs.scale(-S::one()))
+            .into_lin(CS::one());
+
+        // (lhs - rhs) * 1 = 0
+        cs.enforce(
+            || "lhs == rhs",
+            |lc| lc + &diff,
+            |lc| lc + CS::one(),
+            |lc| lc,
+        );
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_expression_parse() {
+    use bls12_381::Scalar;
+
+    let e = Expression::<Scalar>::parse("x*y + z^2 - 3 == out", &["out"]).unwrap();
+    assert_eq!(e.public(), ["out"]);
+    assert_eq!(e.private(), ["x", "y", "z"]);
+
+    let values: HashMap<String, Scalar> = [("x", 2u64), ("y", 3), ("z", 4), ("out", 19)]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), Scalar::from(v)))
+        .collect();
+    assert!(e.is_satisfied(&values).unwrap());
+    assert_eq!(e.public_inputs(&values).unwrap(), [Scalar::from(19u64)]);
+
+    // Unary minus binds looser than `^`, and `*` tighter than `-`.
+    let e = Expression::<Scalar>::parse("-x^2 - 2*-x == (1 - x) * x + x", &[]).unwrap();
+    assert!(e.is_satisfied(&values).unwrap());
+
+    for bad in [
+        "x +",
+        "x == y == z",
+        "x + y",
+        "x ^ y == 1",
+        "(x == 1",
+        "x $ 1 == 2",
+        "x = 1",
+        "2x == 1",
+    ] {
+        assert!(Expression::<Scalar>::parse(bad, &[]).is_err(), "{}", bad);
+    }
+    assert!(Expression::<Scalar>::parse("x == 1", &["y"]).is_err());
+}
+
+#[test]
+fn test_expression_circuit() {
+    use bellman::gadgets::test::TestConstraintSystem;
+    use bls12_381::Scalar;
+
+    let e = Expression::<Scalar>::parse("x*y + z^2 - 3 == out", &["out"]).unwrap();
+    let values = |out: u64| -> HashMap<String, Scalar> {
+        [("x", 2u64), ("y", 3), ("z", 4), ("out", out)]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), Scalar::from(v)))
+            .collect()
+    };
+
+    let mut cs = TestConstraintSystem::new();
+    ExpressionCircuit {
+        expression: &e,
+        values: values(19),
+    }
+    .synthesize(&mut cs)
+    .unwrap();
+
+    assert!(cs.is_satisfied());
+    // x*y, z^2 and the equation itself.
+    assert_eq!(cs.num_constraints(), 3);
+    assert_eq!(cs.get_input(1, "out"), Scalar::from(19u64));
+
+    let mut cs = TestConstraintSystem::new();
+    ExpressionCircuit {
+        expression: &e,
+        values: values(20),
+    }
+    .synthesize(&mut cs)
+    .unwrap();
+    assert_eq!(cs.which_is_unsatisfied(), Some("lhs == rhs"));
+
+    // x^5 by square and multiply: x^2, x^4, x * x^4, plus the equation.
+    let e = Expression::<Scalar>::parse("x^5 == out", &["out"]).unwrap();
+    let mut cs = TestConstraintSystem::new();
+    ExpressionCircuit {
+        expression: &e,
+        values: values(32),
+    }
+    .synthesize(&mut cs)
+    .unwrap();
+    assert!(cs.is_satisfied());
+    assert_eq!(cs.num_constraints(), 4);
+}
+
+#[test]
+fn test_expression_proof() {
+    use bellman::groth16::{
+        create_random_proof, generate_random_parameters, prepare_verifying_key, verify_proof,
+    };
+    use bls12_381::{Bls12, Scalar};
+
+    use crate::rng::seeded;
+
+    let mut rng = seeded(0);
+
+    // The cube example, written as an equation.
+    let e = Expression::<Scalar>::parse("x^3 + x + 5 == out", &["out"]).unwrap();
+
+    let params = {
+        let c = ExpressionCircuit {
+            expression: &e,
+            values: HashMap::new(),
+        };
+        generate_random_parameters::<Bls12, _, _>(c, &mut rng).unwrap()
+    };
+    let pvk = prepare_verifying_key(&params.vk);
+
+    let values: HashMap<String, Scalar> = [("x", 3u64), ("out", 35)]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), Scalar::from(v)))
+        .collect();
+    let inputs = e.public_inputs(&values).unwrap();
+    let c = ExpressionCircuit {
+        expression: &e,
+        values,
+    };
+    let proof = create_random_proof(c, &params, &mut rng).unwrap();
+
+    assert!(verify_proof(&pvk, &proof, &inputs).is_ok());
+    assert!(verify_proof(&pvk, &proof, &[Scalar::from(36u64)]).is_err());
+}
@@ -0,0 +1,74 @@
+use std::path::Path;
+
+use bls12_381::Scalar;
+
+use crate::encode::{self, OutputFiles};
+use crate::error::Result;
+use crate::inputs::{self, Inputs};
+use crate::params;
+use crate::registry;
+use crate::rng::seeded;
+
+/// Seed of the keys and proofs committed under `file/fixtures/`.
+pub const FIXTURE_SEED: u64 = 0;
+
+/// Writes the committed fixtures into `dir`: `cube` parameters, its
+/// verifying key, a proof for `x = 3` with its public inputs, and every
+/// export of them. Setup and proving each start from `FIXTURE_SEED`, so
+/// this writes the same files as the following commands do under `file/`,
+/// the CLI's default directory:
+///
+/// ```text
+/// setup --seed 0
+/// prove --seed 0 --witness x=3
+/// export --public-inputs file/public_inputs.json
+/// ```
+pub fn generate(dir: &Path) -> Result<()> {
+    let spec = registry::lookup("cube")?;
+    std::fs::create_dir_all(dir)?;
+
+    let params = spec.parameters(&mut seeded(FIXTURE_SEED))?;
+    params::save_parameters(dir.join("params.bin"), &params)?;
+    params::save_verifying_key(dir.join("vkey.bin"), &params.vk)?;
+
+    let mut witness = Inputs::new();
+    witness.insert("x", Scalar::from(3u64));
+    let (proof, public) = spec.prove(&params, &witness, &mut seeded(FIXTURE_SEED))?;
+    params::save_proof(dir.join("proof.bin"), &proof)?;
+    inputs::save_inputs(
+        dir.join("public_inputs.json"),
+        &Inputs::from_values(&spec.public_inputs(), &public),
+    )?;
+
+    encode::export(
+        &params.vk,
+        &proof,
+        &public,
+        &OutputFiles::new(dir, spec.name(), "0"),
+    )
+}
+
+#[test]
+fn test_fixtures() {
+    use std::fs;
+
+    // Fails when a fixture under `file/fixtures/` is missing or differs from
+    // what `generate` writes, e.g. after a change to an export format.
+    // Regenerate them with the `fixtures` command. They live apart from
+    // `file/` so that running the CLI with its default paths cannot
+    // overwrite them.
+    let dir = tempfile::tempdir().unwrap();
+    generate(dir.path()).unwrap();
+
+    let committed = Path::new(env!("CARGO_MANIFEST_DIR")).join("file/fixtures");
+    for entry in fs::read_dir(dir.path()).unwrap() {
+        let name = entry.unwrap().file_name();
+        let expected = fs::read(committed.join(&name))
+            .unwrap_or_else(|e| panic!("{:?} is missing: {}", name, e));
+        assert!(
+            expected == fs::read(dir.path().join(&name)).unwrap(),
+            "{:?} is out of date",
+            name
+        );
+    }
+}
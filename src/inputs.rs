@@ -0,0 +1,200 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use bls12_381::Scalar;
+
+use crate::encode::{scalar_from_str_radix, scalar_to_decimal};
+use crate::error::{Error, Result};
+
+/// Parses a canonical field element written in decimal, or in hex with a
+/// `0x` prefix.
+pub fn parse_scalar(s: &str) -> Result<Scalar> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => {
+            scalar_from_str_radix(hex, 16).map_err(|_| Error::InvalidScalar(s.to_string()))
+        }
+        None => scalar_from_str_radix(s, 10),
+    }
+}
+
+/// Named field elements, such as the witness or the public inputs of a
+/// circuit. On disk they are a JSON object of decimal or hex strings,
+/// e.g. `{"x": "3"}` or `{"out": "0x23"}`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Inputs {
+    values: BTreeMap<String, Scalar>,
+}
+
+impl Inputs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pairs each of `names` with the value at the same position.
+    pub fn from_values(names: &[String], values: &[Scalar]) -> Self {
+        Inputs {
+            values: names.iter().cloned().zip(values.iter().copied()).collect(),
+        }
+    }
+
+    pub fn insert(&mut self, name: &str, value: Scalar) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    /// The value called `name`, or `Error::MissingWitness`.
+    pub fn get(&self, name: &str) -> Result<Scalar> {
+        self.values
+            .get(name)
+            .copied()
+            .ok_or_else(|| Error::MissingWitness(name.to_string()))
+    }
+
+    /// The value called `name`, which must be 0 or 1.
+    pub fn get_bool(&self, name: &str) -> Result<bool> {
+        let value = self.get(name)?;
+        if value == Scalar::zero() {
+            Ok(false)
+        } else if value == Scalar::one() {
+            Ok(true)
+        } else {
+            Err(Error::InvalidScalar(format!("{}: expected 0 or 1", name)))
+        }
+    }
+
+    /// The values called `names`, in that order, such as the public inputs
+    /// of a circuit in the order the verifier takes them.
+    pub fn values(&self, names: &[String]) -> Result<Vec<Scalar>> {
+        names.iter().map(|name| self.get(name)).collect()
+    }
+
+    /// Checks that every value in `other` that is also given here agrees,
+    /// e.g. that public inputs supplied along with a witness are the ones
+    /// the witness implies.
+    pub fn check_consistent(&self, other: &Inputs) -> Result<()> {
+        for (name, value) in &other.values {
+            if self.values.get(name).is_some_and(|v| v != value) {
+                return Err(Error::InconsistentInput(name.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds `name=value` pairs, replacing values already present.
+    pub fn extend_from_args(&mut self, args: &[String]) -> Result<()> {
+        for arg in args {
+            let (name, value) = arg.split_once('=').ok_or_else(|| {
+                Error::InvalidFormat(format!("expected NAME=VALUE, got `{}`", arg))
+            })?;
+            self.insert(name, parse_scalar(value)?);
+        }
+        Ok(())
+    }
+}
+
+pub fn read_inputs<R: Read>(reader: R) -> Result<Inputs> {
+    let strings: BTreeMap<String, String> = serde_json::from_reader(reader)?;
+    let mut inputs = Inputs::new();
+    for (name, value) in strings {
+        inputs.insert(&name, parse_scalar(&value)?);
+    }
+    Ok(inputs)
+}
+
+/// Writes `inputs` as a JSON object of decimal strings.
+pub fn write_inputs<W: Write>(inputs: &Inputs, mut writer: W) -> Result<()> {
+    let strings: BTreeMap<&str, String> = inputs
+        .values
+        .iter()
+        .map(|(name, value)| (name.as_str(), scalar_to_decimal(value)))
+        .collect();
+    serde_json::to_writer_pretty(&mut writer, &strings)?;
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn load_inputs<P: AsRef<Path>>(path: P) -> Result<Inputs> {
+    read_inputs(BufReader::new(File::open(path)?))
+}
+
+pub fn save_inputs<P: AsRef<Path>>(path: P, inputs: &Inputs) -> Result<()> {
+    write_inputs(inputs, BufWriter::new(File::create(path)?))
+}
+
+#[test]
+fn test_inputs() {
+    let inputs = read_inputs(&br#"{"x": "3", "bit": "1", "h": "0x1F"}"#[..]).unwrap();
+    assert_eq!(inputs.get("x").unwrap(), Scalar::from(3u64));
+    assert_eq!(inputs.get("h").unwrap(), Scalar::from(31u64));
+    assert!(inputs.get_bool("bit").unwrap());
+    assert!(inputs.get_bool("x").is_err());
+    assert!(matches!(inputs.get("y"), Err(Error::MissingWitness(_))));
+    assert_eq!(
+        inputs.values(&["h".to_string(), "x".to_string()]).unwrap(),
+        [Scalar::from(31u64), Scalar::from(3u64)]
+    );
+
+    let mut inputs = inputs;
+    inputs
+        .extend_from_args(&["x=4".to_string(), "y=0x5".to_string()])
+        .unwrap();
+    assert_eq!(inputs.get("x").unwrap(), Scalar::from(4u64));
+    assert_eq!(inputs.get("y").unwrap(), Scalar::from(5u64));
+    assert!(inputs.extend_from_args(&["z".to_string()]).is_err());
+
+    let mut buf = vec![];
+    write_inputs(&inputs, &mut buf).unwrap();
+    assert_eq!(read_inputs(&buf[..]).unwrap(), inputs);
+
+    assert!(read_inputs(&br#"{"x": "nope"}"#[..]).is_err());
+    assert!(read_inputs(&br#"{"x": 3}"#[..]).is_err());
+    assert!(read_inputs(&br#"["3"]"#[..]).is_err());
+}
+
+#[test]
+fn test_parse_scalar() {
+    let minus_one = -Scalar::one();
+    let decimal = scalar_to_decimal(&minus_one);
+    let hex = format!(
+        "0x{}",
+        minus_one
+            .to_bytes()
+            .iter()
+            .rev()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>()
+    );
+    assert_eq!(parse_scalar(&decimal).unwrap(), minus_one);
+    assert_eq!(parse_scalar(&hex).unwrap(), minus_one);
+    assert_eq!(parse_scalar("0X0a").unwrap(), Scalar::from(10u64));
+
+    // The group order itself is not canonical, in either base.
+    let order = num_bigint::BigUint::parse_bytes(decimal.as_bytes(), 10).unwrap() + 1u32;
+    assert!(parse_scalar(&order.to_str_radix(10)).is_err());
+    assert!(parse_scalar(&format!("0x{}", order.to_str_radix(16))).is_err());
+    assert!(parse_scalar("0x").is_err());
+    assert!(parse_scalar("0xg").is_err());
+
+    // Signs and separators that big integer parsing would let through.
+    for s in ["+1", "+1_0", "1_0", "-1", " 1", "0x+a", "0x1_0"] {
+        assert!(parse_scalar(s).is_err(), "{}", s);
+    }
+}
+
+#[test]
+fn test_check_consistent() {
+    let names = ["out".to_string()];
+    let implied = Inputs::from_values(&names, &[Scalar::from(35u64)]);
+
+    let mut witness = Inputs::new();
+    witness.insert("x", Scalar::from(3u64));
+    assert!(witness.check_consistent(&implied).is_ok());
+    witness.insert("out", Scalar::from(35u64));
+    assert!(witness.check_consistent(&implied).is_ok());
+    witness.insert("out", Scalar::from(36u64));
+    assert!(matches!(
+        witness.check_consistent(&implied),
+        Err(Error::InconsistentInput(_))
+    ));
+}
@@ -0,0 +1,400 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use bellman::{Circuit, ConstraintSystem, Index, LinearCombination, SynthesisError, Variable};
+use bls12_381::Scalar;
+use serde::{Deserialize, Serialize};
+
+use crate::encode::scalar_to_decimal;
+use crate::error::Result;
+
+/// Name of the constant input every constraint system starts with.
+pub const ONE: &str = "ONE";
+
+/// Joins the namespace stack and `name` into a path like `round 5/tmp`.
+pub(crate) fn compute_path(namespace: &[String], name: String) -> String {
+    namespace
+        .iter()
+        .map(String::as_str)
+        .chain(Some(name.as_str()))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// One recorded `A * B = C` constraint.
+#[derive(Clone)]
+pub struct Constraint {
+    pub name: String,
+    pub a: LinearCombination<Scalar>,
+    pub b: LinearCombination<Scalar>,
+    pub c: LinearCombination<Scalar>,
+}
+
+/// A `ConstraintSystem` that records every variable and constraint a
+/// circuit allocates, under its full namespaced name. It never asks for
+/// assignments, so circuits can be recorded without a witness.
+#[derive(Clone)]
+pub struct Recorder {
+    inputs: Vec<String>,
+    aux: Vec<String>,
+    constraints: Vec<Constraint>,
+    namespace: Vec<String>,
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Recorder {
+            inputs: vec![ONE.to_string()],
+            aux: vec![],
+            constraints: vec![],
+            namespace: vec![],
+        }
+    }
+}
+
+/// Records `circuit` with a fresh `Recorder`.
+pub fn record<C: Circuit<Scalar>>(circuit: C) -> Result<Recorder> {
+    let mut recorder = Recorder::new();
+    circuit.synthesize(&mut recorder)?;
+    Ok(recorder)
+}
+
+/// Size of a circuit as synthesized. Groth16 setup adds one more
+/// constraint per input on top of `constraints`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct Metrics {
+    pub constraints: usize,
+    pub aux: usize,
+    /// Public inputs, including `ONE`.
+    pub inputs: usize,
+    /// Terms across the A, B and C linear combinations of every constraint.
+    pub terms: usize,
+}
+
+/// Synthesizes `circuit`, which needs no witness, and measures it.
+pub fn metrics<C: Circuit<Scalar>>(circuit: C) -> Result<Metrics> {
+    Ok(record(circuit)?.metrics())
+}
+
+impl fmt::Display for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} constraints, {} inputs (including {}), {} aux variables, {} terms",
+            self.constraints, self.inputs, ONE, self.aux, self.terms
+        )
+    }
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Public inputs, including `ONE`.
+    pub fn num_inputs(&self) -> usize {
+        self.inputs.len()
+    }
+
+    pub fn num_aux(&self) -> usize {
+        self.aux.len()
+    }
+
+    pub fn num_constraints(&self) -> usize {
+        self.constraints.len()
+    }
+
+    pub fn inputs(&self) -> &[String] {
+        &self.inputs
+    }
+
+    pub fn aux(&self) -> &[String] {
+        &self.aux
+    }
+
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.constraints
+    }
+
+    pub fn metrics(&self) -> Metrics {
+        Metrics {
+            constraints: self.num_constraints(),
+            aux: self.num_aux(),
+            inputs: self.num_inputs(),
+            terms: self
+                .constraints
+                .iter()
+                .map(|c| c.a.as_ref().len() + c.b.as_ref().len() + c.c.as_ref().len())
+                .sum(),
+        }
+    }
+
+    /// The name of `index`.
+    pub fn variable_name(&self, index: Index) -> &str {
+        match index {
+            Index::Input(i) => &self.inputs[i],
+            Index::Aux(i) => &self.aux[i],
+        }
+    }
+
+    /// Position of `index` in the witness vector `[inputs..., aux...]`.
+    fn witness_index(&self, index: Index) -> usize {
+        match index {
+            Index::Input(i) => i,
+            Index::Aux(i) => self.inputs.len() + i,
+        }
+    }
+
+    fn r1cs_terms(&self, lc: &LinearCombination<Scalar>) -> Vec<(usize, String)> {
+        lc.as_ref()
+            .iter()
+            .map(|(var, coeff)| {
+                (
+                    self.witness_index(var.get_unchecked()),
+                    scalar_to_decimal(coeff),
+                )
+            })
+            .collect()
+    }
+
+    pub fn to_r1cs(&self) -> R1cs {
+        R1cs {
+            num_inputs: self.num_inputs(),
+            num_aux: self.num_aux(),
+            variables: self.inputs.iter().chain(&self.aux).cloned().collect(),
+            constraints: self
+                .constraints
+                .iter()
+                .map(|c| R1csConstraint {
+                    name: c.name.clone(),
+                    a: self.r1cs_terms(&c.a),
+                    b: self.r1cs_terms(&c.b),
+                    c: self.r1cs_terms(&c.c),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for Recorder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.metrics().fmt(f)
+    }
+}
+
+impl ConstraintSystem<Scalar> for Recorder {
+    type Root = Self;
+
+    fn alloc<F, A, AR>(
+        &mut self,
+        annotation: A,
+        _f: F,
+    ) -> std::result::Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> std::result::Result<Scalar, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.aux
+            .push(compute_path(&self.namespace, annotation().into()));
+        Ok(Variable::new_unchecked(Index::Aux(self.aux.len() - 1)))
+    }
+
+    fn alloc_input<F, A, AR>(
+        &mut self,
+        annotation: A,
+        _f: F,
+    ) -> std::result::Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> std::result::Result<Scalar, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.inputs
+            .push(compute_path(&self.namespace, annotation().into()));
+        Ok(Variable::new_unchecked(Index::Input(self.inputs.len() - 1)))
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, annotation: A, a: LA, b: LB, c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+        LB: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+        LC: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+    {
+        self.constraints.push(Constraint {
+            name: compute_path(&self.namespace, annotation().into()),
+            a: a(LinearCombination::zero()),
+            b: b(LinearCombination::zero()),
+            c: c(LinearCombination::zero()),
+        });
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.namespace.push(name_fn().into());
+    }
+
+    fn pop_namespace(&mut self) {
+        self.namespace.pop();
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+}
+
+/// A recorded constraint system as JSON. Variables are numbered by their
+/// position in the witness vector: `ONE`, the other public inputs, then
+/// the aux variables. Each linear combination is a list of
+/// `[variable, coefficient]` pairs with decimal coefficients.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct R1cs {
+    pub num_inputs: usize,
+    pub num_aux: usize,
+    pub variables: Vec<String>,
+    pub constraints: Vec<R1csConstraint>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct R1csConstraint {
+    pub name: String,
+    pub a: Vec<(usize, String)>,
+    pub b: Vec<(usize, String)>,
+    pub c: Vec<(usize, String)>,
+}
+
+pub fn write_r1cs<W: Write>(r1cs: &R1cs, mut writer: W) -> Result<()> {
+    serde_json::to_writer_pretty(&mut writer, r1cs)?;
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn save_r1cs<P: AsRef<Path>>(path: P, r1cs: &R1cs) -> Result<()> {
+    write_r1cs(r1cs, BufWriter::new(File::create(path)?))
+}
+
+#[test]
+fn test_record_cube() {
+    use crate::cube::CubeDemo;
+
+    let recorder = record(CubeDemo::<Scalar> { x: None }).unwrap();
+    assert_eq!(recorder.inputs(), ["ONE", "out"]);
+    assert_eq!(recorder.aux(), ["x", "tmp_1", "x_cubed"]);
+    assert_eq!(recorder.num_constraints(), 3);
+    assert_eq!(
+        recorder.to_string(),
+        "3 constraints, 2 inputs (including ONE), 3 aux variables, 11 terms"
+    );
+
+    // out = (x_cubed + x + 5) * ONE
+    let r1cs = recorder.to_r1cs();
+    assert_eq!(
+        r1cs.variables,
+        ["ONE", "out", "x", "tmp_1", "x_cubed"].map(String::from)
+    );
+    let out = &r1cs.constraints[2];
+    assert_eq!(out.name, "out");
+    assert_eq!(out.a, [(4, "1".into()), (2, "1".into()), (0, "5".into())]);
+    assert_eq!(out.b, [(0, "1".into())]);
+    assert_eq!(out.c, [(1, "1".into())]);
+
+    let mut buf = vec![];
+    write_r1cs(&r1cs, &mut buf).unwrap();
+    assert_eq!(serde_json::from_slice::<R1cs>(&buf).unwrap(), r1cs);
+}
+
+#[test]
+fn test_record_mimc() {
+    use crate::mimc::{MiMCDemo, MimcParams, MIMC_SEED};
+
+    let mimc_params = MimcParams::<Scalar>::from_keccak_chain(MIMC_SEED, 10);
+    let recorder = record(MiMCDemo {
+        xl: None,
+        xr: None,
+        params: &mimc_params,
+    })
+    .unwrap();
+
+    assert_eq!(recorder.num_constraints(), 2 * 10);
+    assert_eq!(recorder.inputs(), ["ONE", "mimc/round 9/image"]);
+    assert!(recorder.aux().iter().any(|v| v == "mimc/round 5/tmp"));
+    assert_eq!(
+        recorder.constraints()[2 * 9 + 1].name,
+        "mimc/round 9/new_xL = xR + (xL + Ci)^3"
+    );
+    let names: Vec<_> = recorder.constraints()[1]
+        .c
+        .as_ref()
+        .iter()
+        .map(|(var, _)| recorder.variable_name(var.get_unchecked()))
+        .collect();
+    assert_eq!(names, ["mimc/round 0/new_xl/num", "preimage xr/num"]);
+}
+
+#[test]
+fn test_metrics() {
+    use crate::cube::CubeDemo;
+    use crate::mimc::{MiMCDemo, MimcParams, MIMC_ROUNDS, MIMC_SEED};
+    use crate::registry;
+
+    // The comment in `CubeDemo` flattens into four equations, but
+    // `y + x = tmp_2` is folded into the linear combination of the last one,
+    // so only three constraints are synthesized.
+    let cube = Metrics {
+        constraints: 3,
+        aux: 3,
+        inputs: 2,
+        terms: 11,
+    };
+    assert_eq!(metrics(CubeDemo::<Scalar> { x: None }).unwrap(), cube);
+
+    // With r = 322 rounds: two constraints, two variables and ten terms per
+    // round, on top of the two preimage variables, less the image, which the
+    // last round allocates as the public input. So 2r, 2 + 2r - 1 and 10r.
+    let mimc_params = MimcParams::<Scalar>::from_keccak_chain(MIMC_SEED, MIMC_ROUNDS);
+    let mimc = Metrics {
+        constraints: 644,
+        aux: 645,
+        inputs: 2,
+        terms: 3220,
+    };
+    assert_eq!(
+        metrics(MiMCDemo {
+            xl: None,
+            xr: None,
+            params: &mimc_params,
+        })
+        .unwrap(),
+        mimc
+    );
+
+    // With d = 4 levels: the leaf and its hash, then per level a sibling, a
+    // bit with its booleanity constraint, a swap costing two constraints and
+    // two variables, and a hash, less the root, which is the last hash's
+    // public input. So 2r + d(3 + 2r), 1 + 2r + d(4 + 2r) - 1 and
+    // 10r + d(13 + 10r).
+    let merkle = Metrics {
+        constraints: 3232,
+        aux: 3236,
+        inputs: 2,
+        terms: 16152,
+    };
+
+    // Every registered circuit, as the CLI sees it.
+    let expected = [("cube", cube), ("mimc", mimc), ("merkle", merkle)];
+    for (name, metrics) in expected {
+        assert_eq!(
+            registry::lookup(name).unwrap().record().unwrap().metrics(),
+            metrics,
+            "{}",
+            name
+        );
+    }
+}
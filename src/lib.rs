@@ -0,0 +1,17 @@
+pub mod batch;
+pub mod bench;
+pub mod check;
+pub mod cube;
+pub mod encode;
+pub mod error;
+pub mod expr;
+pub mod fixtures;
+pub mod inputs;
+pub mod inspect;
+pub mod merkle;
+pub mod mimc;
+pub mod params;
+pub mod polynomial;
+pub mod registry;
+pub mod rng;
+pub mod solidity;
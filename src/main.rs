@@ -1,48 +1,431 @@
-#![allow(unused_imports)]
-#![allow(unused_variables)]
-use rand::thread_rng;
-use bellman::{Circuit, ConstraintSystem, SynthesisError};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use bellman::groth16::{prepare_verifying_key, verify_proof, Proof};
 use bls12_381::{Bls12, Scalar};
-use ff::PrimeField as Fr;
+use clap::builder::RangedU64ValueParser;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use bellman_example2::error::Result;
+use bellman_example2::inputs::{self, parse_scalar, Inputs};
+use bellman_example2::rng::Randomness;
+use bellman_example2::{bench, encode, fixtures, inspect, params, registry};
+
+/// Groth16 setup, proving, verification and export for the example circuits.
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// bellman's own binary encoding, as written by `setup` and `prove`.
+    Bin,
+    /// The uncompressed JSON written by `export`.
+    Uncompressed,
+    /// snarkjs `verification_key.json` and `proof.json`.
+    Snarkjs,
+}
 
-mod cube;
-// mod mimc;
-mod encode;
+#[derive(Clone, Copy, ValueEnum)]
+enum BenchFormat {
+    Json,
+    Csv,
+}
+
+#[derive(Args)]
+struct RngArgs {
+    /// Seed a deterministic RNG instead of using the operating system's,
+    /// for reproducible output. Never use this for real keys or proofs.
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List the known circuits and their inputs.
+    List,
+    /// Print the constraint counts of a circuit and optionally dump its
+    /// R1CS as JSON.
+    Inspect {
+        /// Name of the circuit.
+        #[arg(long, default_value = "cube")]
+        circuit: String,
+        /// Where to write the R1CS.
+        #[arg(long)]
+        r1cs: Option<PathBuf>,
+    },
+    /// Generate fresh parameters for a circuit.
+    Setup {
+        /// Name of the circuit.
+        #[arg(long, default_value = "cube")]
+        circuit: String,
+        /// Where to write the parameters.
+        #[arg(long, default_value = "./file/params.bin")]
+        params: PathBuf,
+        /// Where to write the standalone verifying key.
+        #[arg(long, default_value = "./file/vkey.bin")]
+        vkey: PathBuf,
+        #[command(flatten)]
+        rng: RngArgs,
+    },
+    /// Create a proof from witness values.
+    Prove {
+        /// Name of the circuit.
+        #[arg(long, default_value = "cube")]
+        circuit: String,
+        /// Parameters produced by `setup`.
+        #[arg(long, default_value = "./file/params.bin")]
+        params: PathBuf,
+        /// JSON object of witness values in decimal or `0x` hex, e.g.
+        /// `{"x": "3"}`. Public inputs given here must match the witness.
+        #[arg(long)]
+        inputs: Option<PathBuf>,
+        /// Witness value as `name=value`, may be repeated. Takes precedence
+        /// over `--inputs`.
+        #[arg(long = "witness", value_name = "NAME=VALUE")]
+        witness: Vec<String>,
+        /// Where to write the proof.
+        #[arg(long, default_value = "./file/proof.bin")]
+        proof: PathBuf,
+        /// Where to write the public inputs, named as the circuit declares
+        /// them.
+        #[arg(long, default_value = "./file/public_inputs.json")]
+        public_inputs: PathBuf,
+        #[command(flatten)]
+        rng: RngArgs,
+    },
+    /// Verify a proof against its public inputs.
+    Verify {
+        /// Verifying key produced by `setup`.
+        #[arg(long, default_value = "./file/vkey.bin")]
+        vkey: PathBuf,
+        /// Proof produced by `prove`.
+        #[arg(long, default_value = "./file/proof.bin")]
+        proof: PathBuf,
+        /// Public input in decimal or `0x` hex, may be repeated.
+        #[arg(long = "public", value_name = "VALUE")]
+        public: Vec<String>,
+        /// snarkjs `public.json` to read the public inputs from instead.
+        #[arg(long, conflicts_with = "public")]
+        public_file: Option<PathBuf>,
+        /// Public inputs written by `prove` to read instead.
+        #[arg(long, conflicts_with_all = ["public", "public_file"])]
+        public_inputs: Option<PathBuf>,
+        /// Name of the circuit, for the layout of `--public-inputs`.
+        #[arg(long, default_value = "cube")]
+        circuit: String,
+        /// Encoding of the key and proof files.
+        #[arg(long, value_enum, default_value_t = Format::Bin)]
+        format: Format,
+    },
+    /// Export a proof and its verifying key as uncompressed JSON, hex,
+    /// snarkjs JSON, a Solidity verifier and its calldata.
+    Export {
+        /// Verifying key produced by `setup`.
+        #[arg(long, default_value = "./file/vkey.bin")]
+        vkey: PathBuf,
+        /// Proof produced by `prove`.
+        #[arg(long, default_value = "./file/proof.bin")]
+        proof: PathBuf,
+        /// Public input in decimal or `0x` hex for `public.json`, may be
+        /// repeated.
+        #[arg(long = "public", value_name = "VALUE")]
+        public: Vec<String>,
+        /// Public inputs written by `prove` to read instead.
+        #[arg(long, conflicts_with = "public")]
+        public_inputs: Option<PathBuf>,
+        /// Directory the exported files are written to.
+        #[arg(long, default_value = "./file")]
+        out_dir: PathBuf,
+        /// Circuit name used as the file name prefix.
+        #[arg(long, default_value = "cube")]
+        circuit: String,
+        /// Identifies this proof among others of the same circuit.
+        #[arg(long, default_value = "0")]
+        proof_id: String,
+    },
+    /// Time parameter generation, proving, verification, batch
+    /// verification and serialization.
+    Bench {
+        /// Name of a circuit to benchmark, may be repeated. Defaults to
+        /// every known circuit.
+        #[arg(long = "circuit")]
+        circuits: Vec<String>,
+        /// Repetitions of each operation other than setup.
+        #[arg(long, default_value_t = 10, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+        samples: usize,
+        /// Repetitions of parameter generation.
+        #[arg(long, default_value_t = 1, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+        setup_samples: usize,
+        /// Proofs per batch verification.
+        #[arg(long, default_value_t = 10, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+        batch_size: usize,
+        #[arg(long, value_enum, default_value_t = BenchFormat::Json)]
+        format: BenchFormat,
+        /// Where to write the results instead of stdout.
+        #[arg(long)]
+        out: Option<PathBuf>,
+        #[command(flatten)]
+        rng: RngArgs,
+    },
+    /// Regenerate the seeded keys, proof and exports committed as
+    /// fixtures.
+    Fixtures {
+        /// Directory the fixtures are written to.
+        #[arg(long, default_value = "./file/fixtures")]
+        out_dir: PathBuf,
+    },
+}
 
 fn main() {
-    use bellman::groth16::{
-        create_random_proof, generate_random_parameters, prepare_verifying_key, verify_proof, Proof,
-    };
-    // This may not be cryptographically safe, use
-    // `OsRng` (for example) in production software.
-    let mut rng = thread_rng();
+    if let Err(e) = run(Cli::parse()) {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    match cli.command {
+        Command::List => {
+            list();
+            Ok(())
+        }
+        Command::Inspect { circuit, r1cs } => inspect(&circuit, r1cs.as_deref()),
+        Command::Setup {
+            circuit,
+            params,
+            vkey,
+            rng,
+        } => setup(&circuit, &params, &vkey, rng.seed),
+        Command::Prove {
+            circuit,
+            params,
+            inputs,
+            witness,
+            proof,
+            public_inputs,
+            rng,
+        } => prove(
+            &circuit,
+            &params,
+            inputs.as_deref(),
+            &witness,
+            &proof,
+            &public_inputs,
+            rng.seed,
+        ),
+        Command::Verify {
+            vkey,
+            proof,
+            public,
+            public_file,
+            public_inputs,
+            circuit,
+            format,
+        } => {
+            let inputs = match public_file {
+                Some(path) => encode::load_snarkjs_public(path)?,
+                None => public_values(&circuit, &public, public_inputs.as_deref())?,
+            };
+            verify(&vkey, &proof, &inputs, format)
+        }
+        Command::Export {
+            vkey,
+            proof,
+            public,
+            public_inputs,
+            out_dir,
+            circuit,
+            proof_id,
+        } => {
+            let inputs = public_values(&circuit, &public, public_inputs.as_deref())?;
+            let files = encode::OutputFiles::new(out_dir, &circuit, &proof_id);
+            export(&vkey, &proof, &inputs, &files)
+        }
+        Command::Bench {
+            circuits,
+            samples,
+            setup_samples,
+            batch_size,
+            format,
+            out,
+            rng,
+        } => {
+            let config = bench::BenchConfig {
+                samples,
+                setup_samples,
+                batch_size,
+            };
+            run_bench(&circuits, &config, format, out.as_deref(), rng.seed)
+        }
+        Command::Fixtures { out_dir } => {
+            fixtures::generate(&out_dir)?;
+            println!("Wrote fixtures to {}", out_dir.display());
+            Ok(())
+        }
+    }
+}
+
+fn list() {
+    for spec in registry::circuits() {
+        println!(
+            "{}: private {}, public {}",
+            spec.name(),
+            spec.private_inputs().join(", "),
+            spec.public_inputs().join(", ")
+        );
+    }
+}
+
+fn inspect(circuit: &str, r1cs: Option<&Path>) -> Result<()> {
+    let recorder = registry::lookup(circuit)?.record()?;
+    println!("{}: {}", circuit, recorder);
+
+    if let Some(path) = r1cs {
+        inspect::save_r1cs(path, &recorder.to_r1cs())?;
+        println!("Wrote R1CS to {}", path.display());
+    }
+
+    Ok(())
+}
+
+fn setup(circuit: &str, params_path: &Path, vkey_path: &Path, seed: Option<u64>) -> Result<()> {
+    let spec = registry::lookup(circuit)?;
+    let mut rng = Randomness::new(seed);
 
     println!("Creating parameters...");
 
-    // Create parameters for our circuit
-    let params = {
-        let c = cube::CubeDemo::<Scalar> {
-            x: None
-        };
+    let params = spec.parameters(&mut rng)?;
+
+    params::save_parameters(params_path, &params)?;
+    params::save_verifying_key(vkey_path, &params.vk)?;
+    println!(
+        "Wrote parameters to {} and verifying key to {}",
+        params_path.display(),
+        vkey_path.display()
+    );
 
-        generate_random_parameters::<Bls12, _, _>(c, &mut rng).unwrap()
+    Ok(())
+}
+
+fn prove(
+    circuit: &str,
+    params: &Path,
+    inputs: Option<&Path>,
+    witness: &[String],
+    path: &Path,
+    public_path: &Path,
+    seed: Option<u64>,
+) -> Result<()> {
+    let spec = registry::lookup(circuit)?;
+    let mut values = match inputs {
+        Some(inputs) => inputs::load_inputs(inputs)?,
+        None => Inputs::new(),
+    };
+    values.extend_from_args(witness)?;
+
+    // Public inputs supplied with the witness must be the ones it implies;
+    // find out before the expensive part.
+    let public = Inputs::from_values(&spec.public_inputs(), &spec.public_values(&values)?);
+    values.check_consistent(&public)?;
+
+    let params = params::load_parameters(params)?;
+    let mut rng = Randomness::new(seed);
+
+    println!("Creating proofs...");
+
+    let (proof, _) = spec.prove(&params, &values, &mut rng)?;
+
+    params::save_proof(path, &proof)?;
+    inputs::save_inputs(public_path, &public)?;
+    println!(
+        "Wrote proof to {} and public inputs to {}",
+        path.display(),
+        public_path.display()
+    );
+
+    Ok(())
+}
+
+fn verify(vkey: &Path, proof: &Path, inputs: &[Scalar], format: Format) -> Result<()> {
+    let (pvk, proof) = match format {
+        Format::Bin => (
+            prepare_verifying_key(&params::load_verifying_key(vkey)?),
+            read_proof(proof)?,
+        ),
+        Format::Uncompressed => (
+            prepare_verifying_key(&encode::load_uncompressed_vkey(vkey)?),
+            encode::load_uncompressed_proof(proof)?,
+        ),
+        Format::Snarkjs => (
+            encode::load_snarkjs_vkey(vkey)?,
+            encode::load_snarkjs_proof(proof)?,
+        ),
     };
-     // Prepare the verification key (for proof verification)
-     let pvk = prepare_verifying_key(&params.vk);
-
-     println!("Creating proofs...");
-     
-     // Create an instance of circuit
-     let c = cube::CubeDemo {
-         x: Fr::from_str_vartime("3")
-     };
-     
-     // Create a groth16 proof with our parameters.
-     let proof = create_random_proof(c, &params, &mut rng).unwrap();
-         
-    assert!(verify_proof(
-         &pvk,
-         &proof,
-         &[Fr::from_str_vartime("35").unwrap()]
-     ).is_ok());
+    verify_proof(&pvk, &proof, inputs)?;
+    println!("Proof is valid");
+
+    Ok(())
+}
+
+fn export(vkey: &Path, proof: &Path, inputs: &[Scalar], files: &encode::OutputFiles) -> Result<()> {
+    let vk = params::load_verifying_key(vkey)?;
+    let proof = read_proof(proof)?;
+    encode::export(&vk, &proof, inputs, files)?;
+    println!("Wrote exports to {}", files.dir.display());
+    Ok(())
+}
+
+fn run_bench(
+    circuits: &[String],
+    config: &bench::BenchConfig,
+    format: BenchFormat,
+    out: Option<&Path>,
+    seed: Option<u64>,
+) -> Result<()> {
+    let specs = if circuits.is_empty() {
+        registry::circuits()
+    } else {
+        circuits
+            .iter()
+            .map(|name| registry::lookup(name))
+            .collect::<Result<_>>()?
+    };
+
+    let mut rng = Randomness::new(seed);
+    let mut measurements = vec![];
+    for spec in specs {
+        eprintln!("Benchmarking {}...", spec.name());
+        measurements.extend(bench::run(&*spec, config, &mut rng)?);
+    }
+
+    let writer: Box<dyn Write> = match out {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(io::stdout().lock()),
+    };
+    match format {
+        BenchFormat::Json => bench::write_json(&measurements, writer),
+        BenchFormat::Csv => bench::write_csv(&measurements, writer),
+    }
+}
+
+fn read_proof(path: &Path) -> Result<Proof<Bls12>> {
+    Ok(Proof::read(BufReader::new(File::open(path)?))?)
+}
+
+/// Public inputs from `--public-inputs`, ordered by the circuit's layout,
+/// or else from `--public`.
+fn public_values(circuit: &str, public: &[String], path: Option<&Path>) -> Result<Vec<Scalar>> {
+    match path {
+        Some(path) => {
+            let spec = registry::lookup(circuit)?;
+            inputs::load_inputs(path)?.values(&spec.public_inputs())
+        }
+        None => public.iter().map(|s| parse_scalar(s)).collect(),
+    }
 }
@@ -0,0 +1,324 @@
+use ff::PrimeField;
+
+use bellman::gadgets::boolean::{AllocatedBit, Boolean};
+use bellman::gadgets::num::AllocatedNum;
+use bellman::{Circuit, ConstraintSystem, SynthesisError};
+
+use crate::error::{Error, Result};
+use crate::mimc::{mimc, mimc_const_gadget, mimc_gadget, mimc_input_gadget, MimcParams};
+
+/// Hashed with every leaf, `leaf_hash(leaf) = mimc(leaf, LEAF_TAG)`.
+pub const LEAF_TAG: u64 = 1;
+
+/// How a leaf enters the tree. Internal nodes are `mimc(left, right)` of
+/// the level below, while the bottom level holds `leaf_hash` of each leaf,
+/// and zero for missing leaves. So neither an empty slot nor an internal
+/// node can be proven to be a leaf without finding a MiMC preimage.
+pub fn leaf_hash<S: PrimeField>(leaf: S, params: &MimcParams<S>) -> S {
+    mimc(leaf, S::from(LEAF_TAG), params)
+}
+
+/// A complete binary Merkle tree over `leaf_hash` of its leaves, whose
+/// parent nodes are `mimc(left, right)`. Missing leaves are zero at the
+/// bottom level, which no leaf hashes to.
+#[derive(Clone, Debug)]
+pub struct MerkleTree<S: PrimeField> {
+    leaves: Vec<S>,
+    /// `levels[0]` holds the leaf hashes and the last level holds the root.
+    levels: Vec<Vec<S>>,
+}
+
+impl<S: PrimeField> MerkleTree<S> {
+    /// Builds a tree of the given depth over `leaves`, or fails with
+    /// `Error::InvalidTree` if they do not fit in `2^depth` slots.
+    pub fn new(leaves: &[S], depth: usize, params: &MimcParams<S>) -> Result<Self> {
+        let width = u32::try_from(depth)
+            .ok()
+            .and_then(|depth| 1usize.checked_shl(depth))
+            .ok_or_else(|| Error::InvalidTree(format!("depth {} is too large", depth)))?;
+        if leaves.len() > width {
+            return Err(Error::InvalidTree(format!(
+                "{} leaves do not fit in a tree of depth {}",
+                leaves.len(),
+                depth
+            )));
+        }
+
+        let mut level: Vec<S> = leaves.iter().map(|l| leaf_hash(*l, params)).collect();
+        level.resize(width, S::zero());
+
+        let mut levels = vec![level];
+        for _ in 0..depth {
+            let next = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| mimc(pair[0], pair[1], params))
+                .collect();
+            levels.push(next);
+        }
+
+        Ok(MerkleTree {
+            leaves: leaves.to_vec(),
+            levels,
+        })
+    }
+
+    pub fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    pub fn root(&self) -> S {
+        self.levels[self.depth()][0]
+    }
+
+    /// The leaf at `index`, or `None` for an empty slot.
+    pub fn leaf(&self, index: usize) -> Option<S> {
+        self.leaves.get(index).copied()
+    }
+
+    /// The authentication path of the leaf at `index`.
+    ///
+    /// Panics if `index` is outside the tree.
+    pub fn path(&self, index: usize) -> MerklePath<S> {
+        assert!(
+            index < self.levels[0].len(),
+            "leaf {} is outside the tree",
+            index
+        );
+
+        let nodes = self.levels[..self.depth()]
+            .iter()
+            .enumerate()
+            .map(|(height, level)| {
+                let i = index >> height;
+                (level[i ^ 1], i & 1 == 1)
+            })
+            .collect();
+
+        MerklePath { nodes }
+    }
+}
+
+/// The siblings on the way from a leaf to the root, lowest first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerklePath<S: PrimeField> {
+    /// Each sibling, and whether the node on the path is the right child.
+    pub nodes: Vec<(S, bool)>,
+}
+
+impl<S: PrimeField> MerklePath<S> {
+    /// Hashes `leaf` up the path, starting from its `leaf_hash`.
+    pub fn root(&self, leaf: S, params: &MimcParams<S>) -> S {
+        let start = leaf_hash(leaf, params);
+        self.nodes.iter().fold(start, |cur, (sibling, is_right)| {
+            if *is_right {
+                mimc(*sibling, cur, params)
+            } else {
+                mimc(cur, *sibling, params)
+            }
+        })
+    }
+}
+
+/// Proves knowledge of a leaf and authentication path hashing to a public
+/// root, hashing the leaf with `leaf_hash` first as `MerkleTree` does. The
+/// depth of the tree is the length of `path`.
+pub struct MerkleMembership<'a, S: PrimeField> {
+    pub leaf: Option<S>,
+    pub path: Vec<Option<(S, bool)>>,
+    pub params: &'a MimcParams<S>,
+}
+
+impl<'a, S: PrimeField> MerkleMembership<'a, S> {
+    /// A circuit without a witness, for parameter generation.
+    pub fn blank(depth: usize, params: &'a MimcParams<S>) -> Self {
+        MerkleMembership {
+            leaf: None,
+            path: vec![None; depth],
+            params,
+        }
+    }
+
+    pub fn new(leaf: S, path: &MerklePath<S>, params: &'a MimcParams<S>) -> Self {
+        MerkleMembership {
+            leaf: Some(leaf),
+            path: path.nodes.iter().copied().map(Some).collect(),
+            params,
+        }
+    }
+}
+
+impl<'a, S: PrimeField> Circuit<S> for MerkleMembership<'a, S> {
+    fn synthesize<CS: ConstraintSystem<S>>(
+        self,
+        cs: &mut CS,
+    ) -> std::result::Result<(), SynthesisError> {
+        let leaf = AllocatedNum::alloc(cs.namespace(|| "leaf"), || {
+            self.leaf.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let mut cur = mimc_const_gadget(
+            cs.namespace(|| "leaf hash"),
+            &leaf,
+            S::from(LEAF_TAG),
+            self.params,
+        )?;
+
+        let depth = self.path.len();
+        for (i, node) in self.path.into_iter().enumerate() {
+            let cs = &mut cs.namespace(|| format!("level {}", i));
+
+            let sibling = AllocatedNum::alloc(cs.namespace(|| "sibling"), || {
+                node.map(|(s, _)| s)
+                    .ok_or(SynthesisError::AssignmentMissing)
+            })?;
+            let is_right = Boolean::from(AllocatedBit::alloc(
+                cs.namespace(|| "is right"),
+                node.map(|(_, b)| b),
+            )?);
+
+            // (cur, sibling) when `cur` is the left child, swapped otherwise.
+            let (left, right) = AllocatedNum::conditionally_reverse(
+                cs.namespace(|| "order"),
+                &cur,
+                &sibling,
+                &is_right,
+            )?;
+
+            // The root is our only public input, allocated as such by the
+            // last hash.
+            if i + 1 == depth {
+                mimc_input_gadget(cs.namespace(|| "hash"), &left, &right, self.params, "root")?;
+                return Ok(());
+            }
+            cur = mimc_gadget(cs.namespace(|| "hash"), &left, &right, self.params)?;
+        }
+
+        // A tree of depth zero is just its leaf hash.
+        cur.inputize(cs.namespace(|| "root"))
+    }
+}
+
+#[test]
+fn test_merkle_tree() {
+    use bls12_381::Scalar;
+
+    use crate::mimc::MIMC_SEED;
+
+    let mimc_params = MimcParams::<Scalar>::from_keccak_chain(MIMC_SEED, 10);
+    let leaves: Vec<Scalar> = (1..=5u64).map(Scalar::from).collect();
+    let tree = MerkleTree::new(&leaves, 3, &mimc_params).unwrap();
+
+    assert_eq!(tree.depth(), 3);
+    assert_eq!(tree.leaf(4), Some(leaves[4]));
+    assert_eq!(tree.leaf(7), None);
+
+    let h = |l, r| mimc(l, r, &mimc_params);
+    let l: Vec<_> = leaves.iter().map(|x| leaf_hash(*x, &mimc_params)).collect();
+    let zero = Scalar::zero();
+    let expected = h(
+        h(h(l[0], l[1]), h(l[2], l[3])),
+        h(h(l[4], zero), h(zero, zero)),
+    );
+    assert_eq!(tree.root(), expected);
+
+    for (i, leaf) in leaves.iter().enumerate() {
+        let path = tree.path(i);
+        assert_eq!(path.nodes.len(), 3);
+        assert_eq!(path.root(*leaf, &mimc_params), tree.root());
+    }
+    assert_ne!(tree.path(1).root(leaves[0], &mimc_params), tree.root());
+
+    // Neither an empty slot nor an internal node passes as a leaf.
+    assert_ne!(tree.path(7).root(zero, &mimc_params), tree.root());
+    let internal = h(l[0], l[1]);
+    let mut upper = tree.path(0);
+    upper.nodes.remove(0);
+    assert_eq!(
+        upper
+            .nodes
+            .iter()
+            .fold(internal, |cur, (sibling, is_right)| {
+                if *is_right {
+                    h(*sibling, cur)
+                } else {
+                    h(cur, *sibling)
+                }
+            }),
+        tree.root()
+    );
+    assert_ne!(upper.root(internal, &mimc_params), tree.root());
+
+    assert!(matches!(
+        MerkleTree::new(&leaves, 2, &mimc_params),
+        Err(Error::InvalidTree(_))
+    ));
+    assert!(matches!(
+        MerkleTree::new(&leaves, usize::BITS as usize, &mimc_params),
+        Err(Error::InvalidTree(_))
+    ));
+}
+
+#[test]
+fn test_merkle_membership() {
+    use bellman::gadgets::test::TestConstraintSystem;
+    use bls12_381::Scalar;
+
+    use crate::mimc::MIMC_SEED;
+
+    let rounds = 10;
+    let mimc_params = MimcParams::<Scalar>::from_keccak_chain(MIMC_SEED, rounds);
+    let leaves: Vec<Scalar> = (1..=8u64).map(Scalar::from).collect();
+    let tree = MerkleTree::new(&leaves, 3, &mimc_params).unwrap();
+
+    for index in [0, 5, 7] {
+        let mut cs = TestConstraintSystem::new();
+        MerkleMembership::new(leaves[index], &tree.path(index), &mimc_params)
+            .synthesize(&mut cs)
+            .unwrap();
+
+        assert!(cs.is_satisfied());
+        // Two per round for the leaf hash. Per level: one booleanity, two
+        // for the swap and two per round. The last hash allocates the root
+        // as the public input.
+        assert_eq!(cs.num_constraints(), 2 * rounds + 3 * (3 + 2 * rounds));
+        assert_eq!(cs.get_input(1, "level 2/hash/round 9/root"), tree.root());
+    }
+
+    // A leaf that is not in the tree does not reach the root.
+    let mut cs = TestConstraintSystem::new();
+    MerkleMembership::new(Scalar::from(9u64), &tree.path(0), &mimc_params)
+        .synthesize(&mut cs)
+        .unwrap();
+    assert_ne!(cs.get_input(1, "level 2/hash/round 9/root"), tree.root());
+}
+
+#[test]
+fn test_merkle_proof() {
+    use bellman::groth16::{
+        create_random_proof, generate_random_parameters, prepare_verifying_key, verify_proof,
+    };
+    use bls12_381::{Bls12, Scalar};
+
+    use crate::mimc::{MIMC_ROUNDS, MIMC_SEED};
+    use crate::rng::seeded;
+
+    let mut rng = seeded(0);
+
+    let mimc_params = MimcParams::<Scalar>::from_keccak_chain(MIMC_SEED, MIMC_ROUNDS);
+    let leaves: Vec<Scalar> = (1..=3u64).map(Scalar::from).collect();
+    let tree = MerkleTree::new(&leaves, 2, &mimc_params).unwrap();
+
+    let params = generate_random_parameters::<Bls12, _, _>(
+        MerkleMembership::blank(tree.depth(), &mimc_params),
+        &mut rng,
+    )
+    .unwrap();
+    let pvk = prepare_verifying_key(&params.vk);
+
+    let c = MerkleMembership::new(leaves[2], &tree.path(2), &mimc_params);
+    let proof = create_random_proof(c, &params, &mut rng).unwrap();
+
+    assert!(verify_proof(&pvk, &proof, &[tree.root()]).is_ok());
+    assert!(verify_proof(&pvk, &proof, &[leaves[2]]).is_err());
+}
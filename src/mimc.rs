@@ -1,33 +1,109 @@
 use ff::PrimeField;
+use rand::RngCore;
+use sha3::{Digest, Keccak256};
 
-use bellman::{Circuit, ConstraintSystem, SynthesisError};
+use bellman::gadgets::num::AllocatedNum;
+use bellman::{Circuit, ConstraintSystem, LinearCombination, SynthesisError, Variable};
 
+/// Number of rounds used by the demo circuits, enough for the Feistel
+/// construction over the BLS12-381 scalar field.
 pub const MIMC_ROUNDS: usize = 322;
 
+/// Seed of the round constants the registry circuits use, the one
+/// circomlib's `mimc7` derives its constants from.
+pub const MIMC_SEED: &str = "mimc";
 
-// For randomness (during paramgen and proof generation)
-use rand::thread_rng;
+/// Reduces a big-endian integer into the field.
+fn field_from_be_bytes<S: PrimeField>(bytes: &[u8]) -> S {
+    let base = S::from(256);
+    bytes
+        .iter()
+        .fold(S::zero(), |acc, b| acc * base + S::from(u64::from(*b)))
+}
 
-// For benchmarking
-use std::time::{Duration, Instant};
+/// The Keccak chain circomlib derives its constants from:
+/// `h_0 = keccak256(seed)`, `h_i = keccak256(h_{i-1})`.
+fn keccak_chain(seed: &str, len: usize) -> Vec<[u8; 32]> {
+    let mut h: [u8; 32] = Keccak256::digest(seed.as_bytes()).into();
+    let mut chain = Vec::with_capacity(len);
+    for _ in 0..len {
+        chain.push(h);
+        h = Keccak256::digest(h).into();
+    }
+    chain
+}
 
-// Bring in some tools for using finite fiels
-use ff::Field;
+/// Round constants of one MiMC instance, one per round.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MimcParams<S: PrimeField> {
+    constants: Vec<S>,
+}
 
-// We're going to use the BLS12-381 pairing-friendly elliptic curve.
-use bls12_381::{Bls12, Scalar};
+impl<S: PrimeField> MimcParams<S> {
+    /// Panics if `constants` is empty, since MiMC needs at least one round.
+    pub fn new(constants: Vec<S>) -> Self {
+        assert!(!constants.is_empty(), "MiMC needs at least one round");
+        MimcParams { constants }
+    }
 
-// We're going to use the Groth16 proving system.
-use bellman::groth16::{
-    batch, create_random_proof, generate_random_parameters, prepare_verifying_key, verify_proof,
-    Proof,
-};
+    /// Draws `rounds` constants from `rng`.
+    pub fn random<R: RngCore>(rounds: usize, rng: &mut R) -> Self {
+        Self::new((0..rounds).map(|_| S::random(&mut *rng)).collect())
+    }
 
+    /// Nothing-up-my-sleeve constants on circomlib's `mimc7` schedule:
+    /// round `i` uses `h_i` of the Keccak chain reduced into `S`, except
+    /// that round 0 uses zero. The same seed gives the same constants on
+    /// every machine.
+    ///
+    /// Only the schedule is shared with circomlib. Its `mimc7` reduces the
+    /// chain into the BN254 scalar field and runs 91 rounds of `x^7`, while
+    /// the permutation here is a Feistel network over the BLS12-381 scalar
+    /// field with `x^3` and, in the registry, `MIMC_ROUNDS` rounds. So
+    /// neither the constants as field elements nor the hashes match.
+    pub fn from_keccak_chain(seed: &str, rounds: usize) -> Self {
+        let mut constants: Vec<S> = keccak_chain(seed, rounds)
+            .iter()
+            .map(|h| field_from_be_bytes(h))
+            .collect();
+        if let Some(first) = constants.first_mut() {
+            *first = S::zero();
+        }
+        Self::new(constants)
+    }
 
-pub fn mimc<S: PrimeField>(mut xl: S, mut xr: S, constants: &[S]) -> S {
-    assert_eq!(constants.len(), MIMC_ROUNDS);
+    /// Like `from_keccak_chain`, but the last round uses zero as well, which
+    /// is circomlib's `mimcsponge` schedule. The same caveat applies:
+    /// circomlib's `MiMCSponge` is a Feistel network with 220 rounds of
+    /// `x^5` over BN254, so the hashes differ.
+    pub fn from_keccak_chain_sponge(seed: &str, rounds: usize) -> Self {
+        let mut params = Self::from_keccak_chain(seed, rounds);
+        if let Some(last) = params.constants.last_mut() {
+            *last = S::zero();
+        }
+        params
+    }
+
+    pub fn rounds(&self) -> usize {
+        self.constants.len()
+    }
+
+    /// The last round's constant and those of the rounds before it.
+    fn split_last(&self) -> (&S, &[S]) {
+        self.constants
+            .split_last()
+            .expect("`new` rejects empty constants")
+    }
+
+    pub fn constants(&self) -> &[S] {
+        &self.constants
+    }
+}
 
-    for c in constants {
+/// The MiMC Feistel permutation on `(xl, xr)`. Each round maps
+/// `xL, xR` to `xR + (xL + Ci)^3, xL`.
+pub fn mimc_feistel<S: PrimeField>(mut xl: S, mut xr: S, params: &MimcParams<S>) -> (S, S) {
+    for c in params.constants() {
         let mut tmp1 = xl;
         tmp1.add_assign(c);
         let mut tmp2 = tmp1.square();
@@ -37,7 +113,45 @@ pub fn mimc<S: PrimeField>(mut xl: S, mut xr: S, constants: &[S]) -> S {
         xl = tmp2;
     }
 
-    xl
+    (xl, xr)
+}
+
+/// The MiMC Feistel permutation on `(xl, xr)`, returning the final `xl`.
+pub fn mimc<S: PrimeField>(xl: S, xr: S, params: &MimcParams<S>) -> S {
+    mimc_feistel(xl, xr, params).0
+}
+
+/// MiMC sponge with a rate of one field element, starting from the zero
+/// state. Each input is added to `xL` and followed by one permutation; the
+/// first output is the resulting `xL`, and every further output is `xL`
+/// after one more permutation.
+///
+/// Panics if `inputs` is empty or `n_outputs` is zero.
+pub fn mimc_sponge<S: PrimeField>(
+    inputs: &[S],
+    n_outputs: usize,
+    params: &MimcParams<S>,
+) -> Vec<S> {
+    assert!(!inputs.is_empty(), "MiMC sponge needs at least one input");
+    assert!(n_outputs > 0, "MiMC sponge needs at least one output");
+
+    let (mut xl, mut xr) = (S::zero(), S::zero());
+    for input in inputs {
+        xl.add_assign(input);
+        (xl, xr) = mimc_feistel(xl, xr, params);
+    }
+
+    let mut outputs = vec![xl];
+    for _ in 1..n_outputs {
+        (xl, xr) = mimc_feistel(xl, xr, params);
+        outputs.push(xl);
+    }
+    outputs
+}
+
+/// Hashes any number of field elements to one with `mimc_sponge`.
+pub fn mimc_hash<S: PrimeField>(inputs: &[S], params: &MimcParams<S>) -> S {
+    mimc_sponge(inputs, 1, params)[0]
 }
 
 /// This is our demo circuit for proving knowledge of the
@@ -46,7 +160,7 @@ pub fn mimc<S: PrimeField>(mut xl: S, mut xr: S, constants: &[S]) -> S {
 pub struct MiMCDemo<'a, S: PrimeField> {
     pub xl: Option<S>,
     pub xr: Option<S>,
-    pub constants: &'a [S],
+    pub params: &'a MimcParams<S>,
 }
 
 /// Our demo circuit implements this `Circuit` trait which
@@ -54,106 +168,330 @@ pub struct MiMCDemo<'a, S: PrimeField> {
 /// synthesize the constraint system.
 impl<'a, S: PrimeField> Circuit<S> for MiMCDemo<'a, S> {
     fn synthesize<CS: ConstraintSystem<S>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
-        assert_eq!(self.constants.len(), MIMC_ROUNDS);
-
         // Allocate the first component of the preimage.
-        let mut xl_value = self.xl;
-        let mut xl = cs.alloc(
-            || "preimage xl",
-            || xl_value.ok_or(SynthesisError::AssignmentMissing),
-        )?;
+        let xl = AllocatedNum::alloc(cs.namespace(|| "preimage xl"), || {
+            self.xl.ok_or(SynthesisError::AssignmentMissing)
+        })?;
 
         // Allocate the second component of the preimage.
-        let mut xr_value = self.xr;
-        let mut xr = cs.alloc(
-            || "preimage xr",
-            || xr_value.ok_or(SynthesisError::AssignmentMissing),
-        )?;
-
-        for i in 0..MIMC_ROUNDS {
-            // xL, xR := xR + (xL + Ci)^3, xL
-            let cs = &mut cs.namespace(|| format!("round {}", i));
-
-            // tmp = (xL + Ci)^2
-            let tmp_value = xl_value.map(|mut e| {
-                e.add_assign(&self.constants[i]);
-                e.square()
-            });
-            let tmp = cs.alloc(
-                || "tmp",
-                || tmp_value.ok_or(SynthesisError::AssignmentMissing),
-            )?;
-
-            cs.enforce(
-                || "tmp = (xL + Ci)^2",
-                |lc| lc + xl + (self.constants[i], CS::one()),
-                |lc| lc + xl + (self.constants[i], CS::one()),
-                |lc| lc + tmp,
-            );
-
-            // new_xL = xR + (xL + Ci)^3
-            // new_xL = xR + tmp * (xL + Ci)
-            // new_xL - xR = tmp * (xL + Ci)
-            let new_xl_value = xl_value.map(|mut e| {
-                e.add_assign(&self.constants[i]);
-                e.mul_assign(&tmp_value.unwrap());
-                e.add_assign(&xr_value.unwrap());
-                e
-            });
-
-            let new_xl = if i == (MIMC_ROUNDS - 1) {
-                // This is the last round, xL is our image and so
-                // we allocate a public input.
-                cs.alloc_input(
-                    || "image",
-                    || new_xl_value.ok_or(SynthesisError::AssignmentMissing),
-                )?
-            } else {
-                cs.alloc(
-                    || "new_xl",
-                    || new_xl_value.ok_or(SynthesisError::AssignmentMissing),
-                )?
-            };
+        let xr = AllocatedNum::alloc(cs.namespace(|| "preimage xr"), || {
+            self.xr.ok_or(SynthesisError::AssignmentMissing)
+        })?;
 
-            cs.enforce(
-                || "new_xL = xR + (xL + Ci)^3",
-                |lc| lc + tmp,
-                |lc| lc + xl + (self.constants[i], CS::one()),
-                |lc| lc + new_xl - xr,
-            );
+        // The image is our only public input, allocated as such by the last
+        // round.
+        mimc_input_gadget(cs.namespace(|| "mimc"), &xl, &xr, self.params, "image")?;
+        Ok(())
+    }
+}
 
-            // xR = xL
-            xr = xl;
-            xr_value = xl_value;
+/// A value inside a circuit as a linear combination of variables, along
+/// with its assignment when there is a witness.
+#[derive(Clone)]
+struct Term<S: PrimeField> {
+    lc: LinearCombination<S>,
+    value: Option<S>,
+}
 
-            // xL = new_xL
-            xl = new_xl;
-            xl_value = new_xl_value;
+impl<S: PrimeField> Term<S> {
+    fn zero() -> Self {
+        Term {
+            lc: LinearCombination::zero(),
+            value: Some(S::zero()),
         }
+    }
 
-        Ok(())
+    fn constant<CS: ConstraintSystem<S>>(c: S) -> Self {
+        Term {
+            lc: LinearCombination::zero() + (c, CS::one()),
+            value: Some(c),
+        }
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        Term {
+            lc: self.lc.clone() + &other.lc,
+            value: self.value.zip(other.value).map(|(a, b)| a + b),
+        }
+    }
+}
+
+impl<S: PrimeField> From<&AllocatedNum<S>> for Term<S> {
+    fn from(num: &AllocatedNum<S>) -> Self {
+        Term {
+            lc: LinearCombination::zero() + num.get_variable(),
+            value: num.get_value(),
+        }
+    }
+}
+
+/// How a round allocates its `new_xL`.
+trait AllocOutput<S: PrimeField> {
+    type Output;
+
+    /// Allocates `new_xL` from its value, returning it along with its
+    /// variable.
+    fn alloc<CS: ConstraintSystem<S>>(
+        self,
+        cs: &mut CS,
+        value: Option<S>,
+    ) -> Result<(Self::Output, Variable), SynthesisError>;
+}
+
+/// A private variable that more constraints can use.
+struct Private;
+
+impl<S: PrimeField> AllocOutput<S> for Private {
+    type Output = AllocatedNum<S>;
+
+    fn alloc<CS: ConstraintSystem<S>>(
+        self,
+        cs: &mut CS,
+        value: Option<S>,
+    ) -> Result<(AllocatedNum<S>, Variable), SynthesisError> {
+        let num = AllocatedNum::alloc(cs.namespace(|| "new_xl"), || {
+            value.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let var = num.get_variable();
+        Ok((num, var))
+    }
+}
+
+/// The public input of the given name.
+struct Public<'a>(&'a str);
+
+impl<'a, S: PrimeField> AllocOutput<S> for Public<'a> {
+    type Output = Variable;
+
+    fn alloc<CS: ConstraintSystem<S>>(
+        self,
+        cs: &mut CS,
+        value: Option<S>,
+    ) -> Result<(Variable, Variable), SynthesisError> {
+        let var = cs.alloc_input(|| self.0, || value.ok_or(SynthesisError::AssignmentMissing))?;
+        Ok((var, var))
+    }
+}
+
+/// One Feistel round, `new_xL = xR + (xL + Ci)^3`, costing two constraints.
+/// `new_xL` is allocated as `output` says.
+fn mimc_round<S, CS, O>(
+    cs: &mut CS,
+    xl: &Term<S>,
+    xr: &Term<S>,
+    c: S,
+    output: O,
+) -> Result<O::Output, SynthesisError>
+where
+    S: PrimeField,
+    CS: ConstraintSystem<S>,
+    O: AllocOutput<S>,
+{
+    // tmp = (xL + Ci)^2
+    let tmp_value = xl.value.map(|mut e| {
+        e.add_assign(&c);
+        e.square()
+    });
+    let tmp = cs.alloc(
+        || "tmp",
+        || tmp_value.ok_or(SynthesisError::AssignmentMissing),
+    )?;
+
+    cs.enforce(
+        || "tmp = (xL + Ci)^2",
+        |lc| lc + &xl.lc + (c, CS::one()),
+        |lc| lc + &xl.lc + (c, CS::one()),
+        |lc| lc + tmp,
+    );
+
+    // new_xL = xR + (xL + Ci)^3
+    // new_xL = xR + tmp * (xL + Ci)
+    // new_xL - xR = tmp * (xL + Ci)
+    let new_xl_value = xl
+        .value
+        .zip(tmp_value)
+        .zip(xr.value)
+        .map(|((xl, tmp), xr)| (xl + c) * tmp + xr);
+    let (new_xl, new_xl_var) = output.alloc(cs, new_xl_value)?;
+
+    cs.enforce(
+        || "new_xL = xR + (xL + Ci)^3",
+        |lc| lc + tmp,
+        |lc| lc + &xl.lc + (c, CS::one()),
+        |lc| lc + new_xl_var - &xr.lc,
+    );
+
+    Ok(new_xl)
+}
+
+/// In-circuit `mimc_feistel`, one namespace per round, returning the final
+/// `xL` and `xR`. Every round but the last allocates its `new_xL`
+/// privately; the final `xL` is allocated as `output` says.
+fn mimc_feistel_gadget<S, CS, O>(
+    cs: &mut CS,
+    xl: Term<S>,
+    xr: Term<S>,
+    params: &MimcParams<S>,
+    output: O,
+) -> Result<(O::Output, Term<S>), SynthesisError>
+where
+    S: PrimeField,
+    CS: ConstraintSystem<S>,
+    O: AllocOutput<S>,
+{
+    let (mut xl, mut xr) = (xl, xr);
+    let (last, rest) = params.split_last();
+    for (i, c) in rest.iter().enumerate() {
+        let cs = &mut cs.namespace(|| format!("round {}", i));
+        let new_xl = mimc_round(cs, &xl, &xr, *c, Private)?;
+
+        xr = xl;
+        xl = Term::from(&new_xl);
+    }
+
+    let cs = &mut cs.namespace(|| format!("round {}", rest.len()));
+    Ok((mimc_round(cs, &xl, &xr, *last, output)?, xl))
+}
+
+/// In-circuit `mimc` over already allocated `xl` and `xr`, returning the
+/// final `xL` as a private variable to feed into more constraints. Costs
+/// two constraints per round. To expose the result, use
+/// `mimc_input_gadget` rather than `inputize`, which costs one more.
+pub fn mimc_gadget<S, CS>(
+    mut cs: CS,
+    xl: &AllocatedNum<S>,
+    xr: &AllocatedNum<S>,
+    params: &MimcParams<S>,
+) -> Result<AllocatedNum<S>, SynthesisError>
+where
+    S: PrimeField,
+    CS: ConstraintSystem<S>,
+{
+    let (image, _) = mimc_feistel_gadget(&mut cs, Term::from(xl), Term::from(xr), params, Private)?;
+    Ok(image)
+}
+
+/// `mimc_gadget` with a constant `xr`, which needs no variable of its own.
+pub fn mimc_const_gadget<S, CS>(
+    mut cs: CS,
+    xl: &AllocatedNum<S>,
+    xr: S,
+    params: &MimcParams<S>,
+) -> Result<AllocatedNum<S>, SynthesisError>
+where
+    S: PrimeField,
+    CS: ConstraintSystem<S>,
+{
+    let (image, _) = mimc_feistel_gadget(
+        &mut cs,
+        Term::from(xl),
+        Term::constant::<CS>(xr),
+        params,
+        Private,
+    )?;
+    Ok(image)
+}
+
+/// In-circuit `mimc` whose final `xL` is allocated directly as the public
+/// input `name`. Costs exactly two constraints per round, one fewer than
+/// `mimc_gadget` followed by `inputize`.
+pub fn mimc_input_gadget<S, CS>(
+    mut cs: CS,
+    xl: &AllocatedNum<S>,
+    xr: &AllocatedNum<S>,
+    params: &MimcParams<S>,
+    name: &str,
+) -> Result<Variable, SynthesisError>
+where
+    S: PrimeField,
+    CS: ConstraintSystem<S>,
+{
+    let (image, _) = mimc_feistel_gadget(
+        &mut cs,
+        Term::from(xl),
+        Term::from(xr),
+        params,
+        Public(name),
+    )?;
+    Ok(image)
+}
+
+/// In-circuit `mimc_sponge` over already allocated inputs. Absorbing an
+/// input costs no constraints of its own; every permutation costs two per
+/// round.
+///
+/// Panics if `inputs` is empty or `n_outputs` is zero.
+pub fn mimc_sponge_gadget<S, CS>(
+    mut cs: CS,
+    inputs: &[AllocatedNum<S>],
+    n_outputs: usize,
+    params: &MimcParams<S>,
+) -> Result<Vec<AllocatedNum<S>>, SynthesisError>
+where
+    S: PrimeField,
+    CS: ConstraintSystem<S>,
+{
+    assert!(!inputs.is_empty(), "MiMC sponge needs at least one input");
+    assert!(n_outputs > 0, "MiMC sponge needs at least one output");
+
+    let (mut xl, mut xr) = (Term::zero(), Term::zero());
+    let mut last = None;
+    for (i, input) in inputs.iter().enumerate() {
+        let cs = &mut cs.namespace(|| format!("absorb {}", i));
+        let (new_xl, new_xr) =
+            mimc_feistel_gadget(cs, xl.add(&Term::from(input)), xr, params, Private)?;
+
+        xl = Term::from(&new_xl);
+        xr = new_xr;
+        last = Some(new_xl);
     }
+
+    let mut outputs = vec![last.unwrap()];
+    for i in 1..n_outputs {
+        let cs = &mut cs.namespace(|| format!("squeeze {}", i));
+        let (new_xl, new_xr) = mimc_feistel_gadget(cs, xl, xr, params, Private)?;
+
+        xl = Term::from(&new_xl);
+        xr = new_xr;
+        outputs.push(new_xl);
+    }
+    Ok(outputs)
+}
+
+/// In-circuit `mimc_hash`.
+pub fn mimc_hash_gadget<S, CS>(
+    cs: CS,
+    inputs: &[AllocatedNum<S>],
+    params: &MimcParams<S>,
+) -> Result<AllocatedNum<S>, SynthesisError>
+where
+    S: PrimeField,
+    CS: ConstraintSystem<S>,
+{
+    Ok(mimc_sponge_gadget(cs, inputs, 1, params)?.remove(0))
 }
 
 #[test]
 fn test_mimc() {
-    // This may not be cryptographically safe, use
-    // `OsRng` (for example) in production software.
-    let mut rng = thread_rng();
+    use bellman::groth16::{
+        create_random_proof, generate_random_parameters, prepare_verifying_key, verify_proof, Proof,
+    };
+    use bls12_381::{Bls12, Scalar};
+    use ff::Field;
 
-    // Generate the MiMC round constants
-    let constants = (0..MIMC_ROUNDS)
-        .map(|_| Scalar::random(&mut rng))
-        .collect::<Vec<_>>();
+    use crate::rng::seeded;
+
+    let mut rng = seeded(0);
 
-    println!("Creating parameters...");
+    // Generate the MiMC round constants
+    let mimc_params = MimcParams::<Scalar>::from_keccak_chain(MIMC_SEED, MIMC_ROUNDS);
 
     // Create parameters for our circuit
     let params = {
         let c = MiMCDemo {
             xl: None,
             xr: None,
-            constants: &constants,
+            params: &mimc_params,
         };
 
         generate_random_parameters::<Bls12, _, _>(c, &mut rng).unwrap()
@@ -162,33 +500,27 @@ fn test_mimc() {
     // Prepare the verification key (for proof verification)
     let pvk = prepare_verifying_key(&params.vk);
 
-    println!("Creating proofs...");
-
-    // Let's benchmark stuff!
-    const SAMPLES: u32 = 50;
-    let mut total_proving = Duration::new(0, 0);
-    let mut total_verifying = Duration::new(0, 0);
+    // Timings live in `bench`; this only checks that proofs round-trip
+    // and verify.
+    const SAMPLES: u32 = 5;
 
-    // Just a place to put the proof data, so we can
-    // benchmark deserialization.
     let mut proof_vec = vec![];
 
     for _ in 0..SAMPLES {
         // Generate a random preimage and compute the image
         let xl = Scalar::random(&mut rng);
         let xr = Scalar::random(&mut rng);
-        let image = mimc(xl, xr, &constants);
+        let image = mimc(xl, xr, &mimc_params);
 
         proof_vec.truncate(0);
 
-        let start = Instant::now();
         {
             // Create an instance of our circuit (with the
             // witness)
             let c = MiMCDemo {
                 xl: Some(xl),
                 xr: Some(xr),
-                constants: &constants,
+                params: &mimc_params,
             };
 
             // Create a groth16 proof with our parameters.
@@ -197,45 +529,35 @@ fn test_mimc() {
             proof.write(&mut proof_vec).unwrap();
         }
 
-        total_proving += start.elapsed();
-
-        let start = Instant::now();
         let proof = Proof::read(&proof_vec[..]).unwrap();
         // Check the proof
         assert!(verify_proof(&pvk, &proof, &[image]).is_ok());
-        total_verifying += start.elapsed();
     }
-    let proving_avg = total_proving / SAMPLES;
-    let proving_avg =
-        proving_avg.subsec_nanos() as f64 / 1_000_000_000f64 + (proving_avg.as_secs() as f64);
-
-    let verifying_avg = total_verifying / SAMPLES;
-    let verifying_avg =
-        verifying_avg.subsec_nanos() as f64 / 1_000_000_000f64 + (verifying_avg.as_secs() as f64);
-
-    println!("Average proving time: {:?} seconds", proving_avg);
-    println!("Average verifying time: {:?} seconds", verifying_avg);
 }
 
 #[test]
 fn batch_verify() {
-    let mut rng = thread_rng();
+    use bellman::groth16::{
+        batch, create_random_proof, generate_random_parameters, prepare_verifying_key, verify_proof,
+    };
+    use bls12_381::{Bls12, Scalar};
+    use ff::Field;
+
+    use crate::rng::seeded;
+
+    let mut rng = seeded(0);
 
     let mut batch = batch::Verifier::new();
 
     // Generate the MiMC round constants
-    let constants = (0..MIMC_ROUNDS)
-        .map(|_| Scalar::random(&mut rng))
-        .collect::<Vec<_>>();
-
-    println!("Creating parameters...");
+    let mimc_params = MimcParams::<Scalar>::from_keccak_chain(MIMC_SEED, MIMC_ROUNDS);
 
     // Create parameters for our circuit
     let params = {
         let c = MiMCDemo {
             xl: None,
             xr: None,
-            constants: &constants,
+            params: &mimc_params,
         };
 
         generate_random_parameters::<Bls12, _, _>(c, &mut rng).unwrap()
@@ -244,81 +566,174 @@ fn batch_verify() {
     // Prepare the verification key (for proof verification)
     let pvk = prepare_verifying_key(&params.vk);
 
-    println!("Creating proofs...");
-
-    // Let's benchmark stuff!
-    const SAMPLES: u32 = 50;
-    let mut total_proving = Duration::new(0, 0);
-    let mut total_verifying = Duration::new(0, 0);
-
-    // Just a place to put the proof data, so we can
-    // benchmark deserialization.
-    let mut proof_vec = vec![];
+    const SAMPLES: u32 = 5;
 
     for _ in 0..SAMPLES {
         // Generate a random preimage and compute the image
         let xl = Scalar::random(&mut rng);
         let xr = Scalar::random(&mut rng);
-        let image = mimc(xl, xr, &constants);
-
-        proof_vec.truncate(0);
-
-        let start = Instant::now();
-        {
-            // Create an instance of our circuit (with the
-            // witness)
-            let c = MiMCDemo {
-                xl: Some(xl),
-                xr: Some(xr),
-                constants: &constants,
-            };
+        let image = mimc(xl, xr, &mimc_params);
 
-            // Create a groth16 proof with our parameters.
-            let proof = create_random_proof(c, &params, &mut rng).unwrap();
-
-            proof.write(&mut proof_vec).unwrap();
-        }
-
-        total_proving += start.elapsed();
-
-        let start = Instant::now();
-        let proof = Proof::read(&proof_vec[..]).unwrap();
+        // Create an instance of our circuit (with the
+        // witness)
+        let c = MiMCDemo {
+            xl: Some(xl),
+            xr: Some(xr),
+            params: &mimc_params,
+        };
 
-        println!("proof: {:?}", proof);
+        // Create a groth16 proof with our parameters.
+        let proof = create_random_proof(c, &params, &mut rng).unwrap();
 
         // Check the proof
         assert!(verify_proof(&pvk, &proof, &[image]).is_ok());
 
-        total_verifying += start.elapsed();
-
         // Queue the proof and inputs for batch verification.
         batch.queue((proof, [image].into()));
     }
 
-    let mut batch_verifying = Duration::new(0, 0);
-    let batch_start = Instant::now();
-
     // Verify this batch for this specific verifying key
     assert!(batch.verify(rng, &params.vk).is_ok());
+}
+
+#[test]
+fn test_mimc_rounds() {
+    use bellman::gadgets::test::TestConstraintSystem;
+    use bls12_381::Scalar;
+
+    for rounds in [1, 10] {
+        let mimc_params = MimcParams::<Scalar>::from_keccak_chain(MIMC_SEED, rounds);
+        let xl = Scalar::from(1u64);
+        let xr = Scalar::from(2u64);
+
+        let mut cs = TestConstraintSystem::new();
+        MiMCDemo {
+            xl: Some(xl),
+            xr: Some(xr),
+            params: &mimc_params,
+        }
+        .synthesize(&mut cs)
+        .unwrap();
+
+        assert!(cs.is_satisfied());
+        // Two per round; the last one allocates the image as the public
+        // input.
+        assert_eq!(cs.num_constraints(), 2 * rounds);
+        assert_eq!(
+            cs.get_input(1, &format!("mimc/round {}/image", rounds - 1)),
+            mimc(xl, xr, &mimc_params)
+        );
+    }
+}
 
-    batch_verifying += batch_start.elapsed();
+#[test]
+fn test_mimc_constants() {
+    use bls12_381::Scalar;
+    use num_bigint::BigUint;
+
+    // Reproducible, and different for different seeds.
+    let a = MimcParams::<Scalar>::from_keccak_chain(MIMC_SEED, MIMC_ROUNDS);
+    assert_eq!(a, MimcParams::from_keccak_chain(MIMC_SEED, MIMC_ROUNDS));
+    assert_eq!(a.constants()[0], Scalar::zero());
+    assert_ne!(
+        a.constants()[1],
+        MimcParams::from_keccak_chain("other", 2).constants()[1]
+    );
+    assert_eq!(
+        a.constants()[..10],
+        MimcParams::<Scalar>::from_keccak_chain(MIMC_SEED, 10).constants()[..]
+    );
 
-    let proving_avg = total_proving / SAMPLES;
-    let proving_avg =
-        proving_avg.subsec_nanos() as f64 / 1_000_000_000f64 + (proving_avg.as_secs() as f64);
+    // The Keccak chain itself matches circomlib's published constants, which
+    // are reduced modulo the BN254 scalar field. Only the chain is shared:
+    // the constants below are reduced into the BLS12-381 scalar field.
+    let bn254 = BigUint::parse_bytes(
+        b"21888242871839275222246405745257275088548364400416034343698204186575808495617",
+        10,
+    )
+    .unwrap();
+    let chain = |seed, i: usize| BigUint::from_bytes_be(&keccak_chain(seed, i + 1)[i]) % &bn254;
+    assert_eq!(
+        chain("mimcsponge", 1).to_str_radix(10),
+        "7120861356467848435263064379192047478074060781135320967663101236819528304084"
+    );
+    assert_eq!(
+        chain("mimc", 1).to_str_radix(10),
+        "20888961410941983456478427210666206549300505294776164667214940546594746570981"
+    );
 
-    let verifying_avg = total_verifying / SAMPLES;
-    let verifying_avg =
-        verifying_avg.subsec_nanos() as f64 / 1_000_000_000f64 + (verifying_avg.as_secs() as f64);
+    // Reducing into the field agrees with big integer arithmetic.
+    let h = keccak_chain("mimcsponge", 2)[1];
+    let r = BigUint::from_bytes_le((-Scalar::one()).to_repr().as_ref()) + 1u32;
+    let expected = BigUint::from_bytes_be(&h) % r;
+    let sponge = MimcParams::<Scalar>::from_keccak_chain_sponge("mimcsponge", 3);
+    assert_eq!(
+        BigUint::from_bytes_le(sponge.constants()[1].to_repr().as_ref()),
+        expected
+    );
+    assert_eq!(sponge.constants()[0], Scalar::zero());
+    assert_eq!(sponge.constants()[2], Scalar::zero());
+}
 
-    let batch_amortized = batch_verifying / SAMPLES;
-    let batch_amortized = batch_amortized.subsec_nanos() as f64 / 1_000_000_000f64
-        + (batch_amortized.as_secs() as f64);
+#[test]
+fn test_mimc_sponge() {
+    use bellman::gadgets::test::TestConstraintSystem;
+    use bls12_381::Scalar;
+
+    let mimc_params = MimcParams::<Scalar>::from_keccak_chain(MIMC_SEED, 10);
+    let values: Vec<Scalar> = (1..=4u64).map(Scalar::from).collect();
+
+    // One input and no extra output is the plain permutation.
+    assert_eq!(
+        mimc_hash(&values[..1], &mimc_params),
+        mimc(values[0], Scalar::zero(), &mimc_params)
+    );
+    assert_ne!(
+        mimc_hash(&values, &mimc_params),
+        mimc_hash(&values[..3], &mimc_params)
+    );
 
-    println!("Average proving time: {:?} seconds", proving_avg);
-    println!("Average verifying time: {:?} seconds", verifying_avg);
-    println!(
-        "Amortized batch verifying time: {:?} seconds",
-        batch_amortized
+    let expected = mimc_sponge(&values, 2, &mimc_params);
+
+    let mut cs = TestConstraintSystem::new();
+    let inputs = values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| AllocatedNum::alloc(cs.namespace(|| format!("input {}", i)), || Ok(*v)))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let outputs = mimc_sponge_gadget(cs.namespace(|| "sponge"), &inputs, 2, &mimc_params).unwrap();
+
+    assert!(cs.is_satisfied());
+    assert_eq!(cs.num_constraints(), 2 * 10 * (values.len() + 1));
+    assert_eq!(
+        outputs
+            .iter()
+            .map(|o| o.get_value().unwrap())
+            .collect::<Vec<_>>(),
+        expected
     );
 }
+
+#[test]
+fn test_mimc_gadget_composes() {
+    use bellman::gadgets::test::TestConstraintSystem;
+    use bls12_381::Scalar;
+
+    let mimc_params = MimcParams::<Scalar>::from_keccak_chain(MIMC_SEED, 10);
+    let (a, b, c) = (Scalar::from(1u64), Scalar::from(2u64), Scalar::from(3u64));
+
+    // mimc(mimc(a, b), c), with only the outer image public.
+    let mut cs = TestConstraintSystem::new();
+    let a_var = AllocatedNum::alloc(cs.namespace(|| "a"), || Ok(a)).unwrap();
+    let b_var = AllocatedNum::alloc(cs.namespace(|| "b"), || Ok(b)).unwrap();
+    let c_var = AllocatedNum::alloc(cs.namespace(|| "c"), || Ok(c)).unwrap();
+    let inner = mimc_gadget(cs.namespace(|| "inner"), &a_var, &b_var, &mimc_params).unwrap();
+    let outer = mimc_gadget(cs.namespace(|| "outer"), &inner, &c_var, &mimc_params).unwrap();
+    outer.inputize(cs.namespace(|| "image")).unwrap();
+
+    let expected = mimc(mimc(a, b, &mimc_params), c, &mimc_params);
+    assert!(cs.is_satisfied());
+    assert_eq!(cs.num_inputs(), 2);
+    assert_eq!(cs.get_input(1, "image/input variable"), expected);
+}
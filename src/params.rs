@@ -0,0 +1,124 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use bellman::groth16::{Parameters, Proof, VerifyingKey};
+use bls12_381::Bls12;
+
+use crate::error::{Error, Result};
+
+/// Magic bytes at the start of a parameters file.
+pub const PARAMS_MAGIC: [u8; 4] = *b"BEGP";
+
+/// Magic bytes at the start of a standalone verifying key file.
+pub const VKEY_MAGIC: [u8; 4] = *b"BEGV";
+
+/// Version of the on-disk layout, bumped whenever the encoding changes.
+pub const FORMAT_VERSION: u32 = 1;
+
+fn write_header<W: Write>(mut writer: W, magic: [u8; 4]) -> Result<()> {
+    writer.write_all(&magic)?;
+    writer.write_all(&FORMAT_VERSION.to_be_bytes())?;
+    Ok(())
+}
+
+fn read_header<R: Read>(mut reader: R, magic: [u8; 4]) -> Result<()> {
+    let mut found = [0u8; 4];
+    reader.read_exact(&mut found)?;
+    if found != magic {
+        return Err(Error::InvalidFormat(format!(
+            "bad magic bytes {:?}, expected {:?}",
+            found, magic
+        )));
+    }
+
+    let mut version = [0u8; 4];
+    reader.read_exact(&mut version)?;
+    let version = u32::from_be_bytes(version);
+    if version != FORMAT_VERSION {
+        return Err(Error::InvalidFormat(format!(
+            "unsupported format version {}, expected {}",
+            version, FORMAT_VERSION
+        )));
+    }
+
+    Ok(())
+}
+
+/// Writes the full Groth16 parameters behind a versioned header.
+pub fn write_parameters<W: Write>(params: &Parameters<Bls12>, mut writer: W) -> Result<()> {
+    write_header(&mut writer, PARAMS_MAGIC)?;
+    params.write(&mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads parameters written by `write_parameters`, checking every curve point.
+pub fn read_parameters<R: Read>(mut reader: R) -> Result<Parameters<Bls12>> {
+    read_header(&mut reader, PARAMS_MAGIC)?;
+    Ok(Parameters::read(reader, true)?)
+}
+
+/// Writes a verifying key on its own behind a versioned header.
+pub fn write_verifying_key<W: Write>(vk: &VerifyingKey<Bls12>, mut writer: W) -> Result<()> {
+    write_header(&mut writer, VKEY_MAGIC)?;
+    vk.write(&mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads a verifying key written by `write_verifying_key`.
+pub fn read_verifying_key<R: Read>(mut reader: R) -> Result<VerifyingKey<Bls12>> {
+    read_header(&mut reader, VKEY_MAGIC)?;
+    Ok(VerifyingKey::read(reader)?)
+}
+
+pub fn save_parameters<P: AsRef<Path>>(path: P, params: &Parameters<Bls12>) -> Result<()> {
+    write_parameters(params, BufWriter::new(File::create(path)?))
+}
+
+pub fn load_parameters<P: AsRef<Path>>(path: P) -> Result<Parameters<Bls12>> {
+    read_parameters(BufReader::new(File::open(path)?))
+}
+
+pub fn save_verifying_key<P: AsRef<Path>>(path: P, vk: &VerifyingKey<Bls12>) -> Result<()> {
+    write_verifying_key(vk, BufWriter::new(File::create(path)?))
+}
+
+pub fn load_verifying_key<P: AsRef<Path>>(path: P) -> Result<VerifyingKey<Bls12>> {
+    read_verifying_key(BufReader::new(File::open(path)?))
+}
+
+/// Writes a proof in bellman's own encoding, without a header.
+pub fn save_proof<P: AsRef<Path>>(path: P, proof: &Proof<Bls12>) -> Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    proof.write(&mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+#[test]
+fn test_params_round_trip() {
+    use bellman::groth16::{prepare_verifying_key, verify_proof};
+
+    use crate::cube;
+    use crate::rng::seeded;
+
+    let (params, proof, public) = cube::test_proof(&mut seeded(0));
+
+    let mut params_vec = vec![];
+    write_parameters(&params, &mut params_vec).unwrap();
+    let mut vk_vec = vec![];
+    write_verifying_key(&params.vk, &mut vk_vec).unwrap();
+
+    // The reloaded parameters are the same, and the proof verifies against
+    // the reloaded key.
+    assert!(read_parameters(&params_vec[..]).unwrap() == params);
+    let vk = read_verifying_key(&vk_vec[..]).unwrap();
+    assert!(verify_proof(&prepare_verifying_key(&vk), &proof, &public).is_ok());
+
+    // Files of the wrong kind or version are rejected.
+    assert!(read_parameters(&vk_vec[..]).is_err());
+    vk_vec[7] += 1;
+    assert!(read_verifying_key(&vk_vec[..]).is_err());
+}
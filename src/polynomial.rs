@@ -0,0 +1,149 @@
+use ff::PrimeField;
+
+use bellman::{Circuit, ConstraintSystem, LinearCombination, SynthesisError};
+
+/// Evaluates the polynomial with coefficients `coeffs`, constant term
+/// first, at `x` with Horner's method.
+pub fn evaluate<S: PrimeField>(coeffs: &[S], x: S) -> S {
+    coeffs.iter().rev().fold(S::zero(), |acc, c| acc * x + c)
+}
+
+/// Proves knowledge of `x` such that `p(x) == out`, with `out` public.
+/// `CubeDemo` is the special case `coeffs = [5, 1, 0, 1]`.
+///
+/// `coeffs[i]` is the coefficient of `x^i`. Horner's method costs one
+/// constraint per degree, plus one tying the result to `out`; the first
+/// step multiplies a constant and is free.
+pub struct PolynomialCircuit<S: PrimeField> {
+    pub coeffs: Vec<S>,
+    pub x: Option<S>,
+}
+
+impl<S: PrimeField> PolynomialCircuit<S> {
+    /// `p(x)`, when there is a witness.
+    pub fn output(&self) -> Option<S> {
+        self.x.map(|x| evaluate(&self.coeffs, x))
+    }
+}
+
+impl<S: PrimeField> Circuit<S> for PolynomialCircuit<S> {
+    fn synthesize<CS: ConstraintSystem<S>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let out_val = self.output();
+
+        // Allocate the private evaluation point
+        let x_val = self.x;
+        let x = cs.alloc(|| "x", || x_val.ok_or(SynthesisError::AssignmentMissing))?;
+
+        // The running Horner value `acc` as a linear combination plus a
+        // constant. It stays a constant until the first multiplication by
+        // `x`, which is then linear and needs no constraint.
+        let mut constant = true;
+        let mut acc_lc = LinearCombination::<S>::zero();
+        let mut acc_const = S::zero();
+        let mut acc_val = Some(S::zero());
+
+        for (i, c) in self.coeffs.iter().enumerate().rev() {
+            // acc = acc * x + c, skipped for the leading coefficient
+            if i + 1 < self.coeffs.len() {
+                acc_val = acc_val.zip(x_val).map(|(a, x)| a * x);
+                if constant {
+                    acc_lc = LinearCombination::zero() + (acc_const, x);
+                    constant = false;
+                } else {
+                    let product = cs.alloc(
+                        || format!("step {}", i),
+                        || acc_val.ok_or(SynthesisError::AssignmentMissing),
+                    )?;
+                    cs.enforce(
+                        || format!("step {} = acc * x", i),
+                        |lc| lc + &acc_lc + (acc_const, CS::one()),
+                        |lc| lc + x,
+                        |lc| lc + product,
+                    );
+                    acc_lc = LinearCombination::zero() + product;
+                }
+                acc_const = S::zero();
+            }
+            acc_const.add_assign(c);
+            acc_val = acc_val.map(|a| a + c);
+        }
+
+        // Allocating the public "primary" output uses alloc_input
+        let out = cs.alloc_input(
+            || "out",
+            || out_val.ok_or(SynthesisError::AssignmentMissing),
+        )?;
+        // acc * 1 = out
+        cs.enforce(
+            || "out = acc",
+            |lc| lc + &acc_lc + (acc_const, CS::one()),
+            |lc| lc + CS::one(),
+            |lc| lc + out,
+        );
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_polynomial_constraints() {
+    use bellman::gadgets::test::TestConstraintSystem;
+    use bls12_381::Scalar;
+
+    let x = Scalar::from(3u64);
+    for degree in 0..6u64 {
+        let coeffs: Vec<Scalar> = (1..=degree + 1).map(Scalar::from).collect();
+        let c = PolynomialCircuit {
+            coeffs: coeffs.clone(),
+            x: Some(x),
+        };
+        let out = c.output().unwrap();
+
+        let mut cs = TestConstraintSystem::new();
+        c.synthesize(&mut cs).unwrap();
+
+        assert!(cs.is_satisfied());
+        assert_eq!(cs.num_constraints() as u64, degree.max(1));
+        assert_eq!(cs.get_input(1, "out"), out);
+
+        let expected = (0..=degree).fold(Scalar::zero(), |acc, i| {
+            acc + Scalar::from(i + 1) * ff::Field::pow_vartime(&x, [i])
+        });
+        assert_eq!(out, expected);
+    }
+}
+
+#[test]
+fn test_polynomial_proof() {
+    use bellman::groth16::{
+        create_random_proof, generate_random_parameters, prepare_verifying_key, verify_proof,
+    };
+    use bls12_381::{Bls12, Scalar};
+    use ff::Field;
+
+    use crate::rng::seeded;
+
+    let mut rng = seeded(0);
+
+    // x^3 + x + 5, the cube example.
+    let coeffs: Vec<Scalar> = [5u64, 1, 0, 1].into_iter().map(Scalar::from).collect();
+
+    let params = {
+        let c = PolynomialCircuit {
+            coeffs: coeffs.clone(),
+            x: None,
+        };
+        generate_random_parameters::<Bls12, _, _>(c, &mut rng).unwrap()
+    };
+    let pvk = prepare_verifying_key(&params.vk);
+
+    let c = PolynomialCircuit {
+        coeffs,
+        x: Some(Scalar::from(3u64)),
+    };
+    assert_eq!(c.output(), Some(Scalar::from(35u64)));
+    let proof = create_random_proof(c, &params, &mut rng).unwrap();
+
+    assert!(verify_proof(&pvk, &proof, &[Scalar::from(35u64)]).is_ok());
+    assert!(verify_proof(&pvk, &proof, &[Scalar::random(&mut rng)]).is_err());
+}
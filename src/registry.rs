@@ -0,0 +1,335 @@
+use bellman::groth16::{create_random_proof, generate_random_parameters, Parameters, Proof};
+use bellman::Circuit;
+use bls12_381::{Bls12, Scalar};
+use ff::Field;
+use rand::{Rng, RngCore};
+
+use crate::cube::CubeDemo;
+use crate::error::{Error, Result};
+use crate::inputs::Inputs;
+use crate::inspect::{self, Recorder};
+use crate::merkle::{MerkleMembership, MerklePath};
+use crate::mimc::{self, MiMCDemo, MimcParams, MIMC_ROUNDS, MIMC_SEED};
+use crate::polynomial;
+
+/// Depth of the tree behind the `merkle` circuit.
+pub const MERKLE_DEPTH: usize = 4;
+
+/// A circuit that can be set up and proven by name. Witness values are
+/// looked up by name in an `Inputs`; the public inputs are returned in the
+/// order a verifier takes them.
+pub trait CircuitSpec {
+    fn name(&self) -> &'static str;
+
+    /// Names of the private witness values the prover supplies.
+    fn private_inputs(&self) -> Vec<String>;
+
+    /// Names of the public inputs, in the order the verifier takes them.
+    fn public_inputs(&self) -> Vec<String>;
+
+    fn parameters(&self, rng: &mut dyn RngCore) -> Result<Parameters<Bls12>>;
+
+    /// Records the variables and constraints of the circuit.
+    fn record(&self) -> Result<Recorder>;
+
+    /// A random valid witness, for benchmarks.
+    fn sample_witness(&self, rng: &mut dyn RngCore) -> Inputs;
+
+    /// The public inputs `witness` implies, in the order the verifier takes
+    /// them, without proving anything.
+    fn public_values(&self, witness: &Inputs) -> Result<Vec<Scalar>>;
+
+    /// Proves the circuit for `witness`, returning the proof along with the
+    /// public inputs it commits to.
+    fn prove(
+        &self,
+        params: &Parameters<Bls12>,
+        witness: &Inputs,
+        rng: &mut dyn RngCore,
+    ) -> Result<(Proof<Bls12>, Vec<Scalar>)>;
+}
+
+fn setup<C: Circuit<Scalar>>(circuit: C, mut rng: &mut dyn RngCore) -> Result<Parameters<Bls12>> {
+    Ok(generate_random_parameters::<Bls12, _, _>(
+        circuit, &mut rng,
+    )?)
+}
+
+fn prove<C: Circuit<Scalar>>(
+    circuit: C,
+    params: &Parameters<Bls12>,
+    mut rng: &mut dyn RngCore,
+) -> Result<Proof<Bls12>> {
+    Ok(create_random_proof(circuit, params, &mut rng)?)
+}
+
+/// The round constants shared by the MiMC based circuits.
+fn mimc_params() -> MimcParams<Scalar> {
+    MimcParams::from_keccak_chain(MIMC_SEED, MIMC_ROUNDS)
+}
+
+fn names(names: &[&str]) -> Vec<String> {
+    names.iter().map(|n| n.to_string()).collect()
+}
+
+/// `cube::CubeDemo`: `x^3 + x + 5 == out`.
+pub struct Cube;
+
+impl CircuitSpec for Cube {
+    fn name(&self) -> &'static str {
+        "cube"
+    }
+
+    fn private_inputs(&self) -> Vec<String> {
+        names(&["x"])
+    }
+
+    fn public_inputs(&self) -> Vec<String> {
+        names(&["out"])
+    }
+
+    fn parameters(&self, rng: &mut dyn RngCore) -> Result<Parameters<Bls12>> {
+        setup(CubeDemo::<Scalar> { x: None }, rng)
+    }
+
+    fn record(&self) -> Result<Recorder> {
+        inspect::record(CubeDemo::<Scalar> { x: None })
+    }
+
+    fn sample_witness(&self, rng: &mut dyn RngCore) -> Inputs {
+        let mut witness = Inputs::new();
+        witness.insert("x", Scalar::random(rng));
+        witness
+    }
+
+    fn public_values(&self, witness: &Inputs) -> Result<Vec<Scalar>> {
+        let coeffs = [5u64, 1, 0, 1].map(Scalar::from);
+        Ok(vec![polynomial::evaluate(&coeffs, witness.get("x")?)])
+    }
+
+    fn prove(
+        &self,
+        params: &Parameters<Bls12>,
+        witness: &Inputs,
+        rng: &mut dyn RngCore,
+    ) -> Result<(Proof<Bls12>, Vec<Scalar>)> {
+        let public = self.public_values(witness)?;
+        let x = witness.get("x")?;
+        Ok((prove(CubeDemo { x: Some(x) }, params, rng)?, public))
+    }
+}
+
+/// `mimc::MiMCDemo` with the default round constants.
+pub struct Mimc;
+
+impl CircuitSpec for Mimc {
+    fn name(&self) -> &'static str {
+        "mimc"
+    }
+
+    fn private_inputs(&self) -> Vec<String> {
+        names(&["xl", "xr"])
+    }
+
+    fn public_inputs(&self) -> Vec<String> {
+        names(&["image"])
+    }
+
+    fn parameters(&self, rng: &mut dyn RngCore) -> Result<Parameters<Bls12>> {
+        let mimc_params = mimc_params();
+        let c = MiMCDemo {
+            xl: None,
+            xr: None,
+            params: &mimc_params,
+        };
+        setup(c, rng)
+    }
+
+    fn record(&self) -> Result<Recorder> {
+        let mimc_params = mimc_params();
+        inspect::record(MiMCDemo {
+            xl: None,
+            xr: None,
+            params: &mimc_params,
+        })
+    }
+
+    fn sample_witness(&self, rng: &mut dyn RngCore) -> Inputs {
+        let mut witness = Inputs::new();
+        witness.insert("xl", Scalar::random(&mut *rng));
+        witness.insert("xr", Scalar::random(rng));
+        witness
+    }
+
+    fn public_values(&self, witness: &Inputs) -> Result<Vec<Scalar>> {
+        let (xl, xr) = (witness.get("xl")?, witness.get("xr")?);
+        Ok(vec![mimc::mimc(xl, xr, &mimc_params())])
+    }
+
+    fn prove(
+        &self,
+        params: &Parameters<Bls12>,
+        witness: &Inputs,
+        rng: &mut dyn RngCore,
+    ) -> Result<(Proof<Bls12>, Vec<Scalar>)> {
+        let public = self.public_values(witness)?;
+        let mimc_params = mimc_params();
+        let c = MiMCDemo {
+            xl: Some(witness.get("xl")?),
+            xr: Some(witness.get("xr")?),
+            params: &mimc_params,
+        };
+        Ok((prove(c, params, rng)?, public))
+    }
+}
+
+/// `merkle::MerkleMembership` over a tree of depth `MERKLE_DEPTH`. The
+/// witness is the `leaf` and, for each level `i` from the leaf up,
+/// `sibling_i` and `is_right_i` (0 or 1).
+pub struct Merkle;
+
+impl CircuitSpec for Merkle {
+    fn name(&self) -> &'static str {
+        "merkle"
+    }
+
+    fn private_inputs(&self) -> Vec<String> {
+        let mut names = vec!["leaf".to_string()];
+        for i in 0..MERKLE_DEPTH {
+            names.push(format!("sibling_{}", i));
+            names.push(format!("is_right_{}", i));
+        }
+        names
+    }
+
+    fn public_inputs(&self) -> Vec<String> {
+        names(&["root"])
+    }
+
+    fn parameters(&self, rng: &mut dyn RngCore) -> Result<Parameters<Bls12>> {
+        let mimc_params = mimc_params();
+        setup(MerkleMembership::blank(MERKLE_DEPTH, &mimc_params), rng)
+    }
+
+    fn record(&self) -> Result<Recorder> {
+        let mimc_params = mimc_params();
+        inspect::record(MerkleMembership::blank(MERKLE_DEPTH, &mimc_params))
+    }
+
+    fn sample_witness(&self, rng: &mut dyn RngCore) -> Inputs {
+        let mut witness = Inputs::new();
+        witness.insert("leaf", Scalar::random(&mut *rng));
+        for i in 0..MERKLE_DEPTH {
+            witness.insert(&format!("sibling_{}", i), Scalar::random(&mut *rng));
+            witness.insert(
+                &format!("is_right_{}", i),
+                Scalar::from(rng.gen::<bool>() as u64),
+            );
+        }
+        witness
+    }
+
+    fn public_values(&self, witness: &Inputs) -> Result<Vec<Scalar>> {
+        let (leaf, path) = merkle_witness(witness)?;
+        Ok(vec![path.root(leaf, &mimc_params())])
+    }
+
+    fn prove(
+        &self,
+        params: &Parameters<Bls12>,
+        witness: &Inputs,
+        rng: &mut dyn RngCore,
+    ) -> Result<(Proof<Bls12>, Vec<Scalar>)> {
+        let mimc_params = mimc_params();
+        let (leaf, path) = merkle_witness(witness)?;
+        let root = path.root(leaf, &mimc_params);
+        let c = MerkleMembership::new(leaf, &path, &mimc_params);
+        Ok((prove(c, params, rng)?, vec![root]))
+    }
+}
+
+/// The leaf and authentication path of a `Merkle` witness.
+fn merkle_witness(witness: &Inputs) -> Result<(Scalar, MerklePath<Scalar>)> {
+    let nodes = (0..MERKLE_DEPTH)
+        .map(|i| {
+            Ok((
+                witness.get(&format!("sibling_{}", i))?,
+                witness.get_bool(&format!("is_right_{}", i))?,
+            ))
+        })
+        .collect::<Result<_>>()?;
+    Ok((witness.get("leaf")?, MerklePath { nodes }))
+}
+
+/// Every circuit known by name.
+pub fn circuits() -> Vec<Box<dyn CircuitSpec>> {
+    vec![Box::new(Cube), Box::new(Mimc), Box::new(Merkle)]
+}
+
+/// The circuit called `name`, or `Error::UnknownCircuit`.
+pub fn lookup(name: &str) -> Result<Box<dyn CircuitSpec>> {
+    circuits()
+        .into_iter()
+        .find(|c| c.name() == name)
+        .ok_or_else(|| Error::UnknownCircuit(name.to_string()))
+}
+
+#[test]
+fn test_registry() {
+    use bellman::groth16::{prepare_verifying_key, verify_proof};
+
+    use crate::merkle::MerkleTree;
+    use crate::rng::seeded;
+
+    let mut rng = seeded(0);
+
+    assert!(matches!(lookup("square"), Err(Error::UnknownCircuit(_))));
+
+    let mut witnesses = vec![];
+
+    let mut cube = Inputs::new();
+    cube.insert("x", Scalar::from(3u64));
+    witnesses.push(("cube", cube, Some(Scalar::from(35u64))));
+
+    let mut mimc = Inputs::new();
+    mimc.insert("xl", Scalar::from(1u64));
+    mimc.insert("xr", Scalar::from(2u64));
+    witnesses.push(("mimc", mimc, None));
+
+    let leaves: Vec<Scalar> = (1..=5u64).map(Scalar::from).collect();
+    let tree = MerkleTree::new(&leaves, MERKLE_DEPTH, &mimc_params()).unwrap();
+    let mut merkle = Inputs::new();
+    merkle.insert("leaf", leaves[3]);
+    for (i, (sibling, is_right)) in tree.path(3).nodes.into_iter().enumerate() {
+        merkle.insert(&format!("sibling_{}", i), sibling);
+        merkle.insert(&format!("is_right_{}", i), Scalar::from(is_right as u64));
+    }
+    witnesses.push(("merkle", merkle, Some(tree.root())));
+
+    for (name, witness, expected) in witnesses {
+        let spec = lookup(name).unwrap();
+        assert_eq!(spec.name(), name);
+        for input in spec.private_inputs() {
+            assert!(witness.get(&input).is_ok(), "{}: {}", name, input);
+        }
+
+        let recorder = spec.record().unwrap();
+        assert_eq!(recorder.num_inputs(), 1 + spec.public_inputs().len());
+
+        let params = spec.parameters(&mut rng).unwrap();
+        let (proof, public) = spec.prove(&params, &witness, &mut rng).unwrap();
+        assert_eq!(public.len(), spec.public_inputs().len());
+        assert_eq!(spec.public_values(&witness).unwrap(), public);
+        if let Some(expected) = expected {
+            assert_eq!(public, [expected]);
+        }
+
+        let pvk = prepare_verifying_key(&params.vk);
+        assert!(verify_proof(&pvk, &proof, &public).is_ok(), "{}", name);
+
+        assert!(matches!(
+            spec.prove(&params, &Inputs::new(), &mut rng),
+            Err(Error::MissingWitness(_))
+        ));
+    }
+}
@@ -0,0 +1,81 @@
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+/// A ChaCha20 generator seeded with `seed`, for tests and for fixtures that
+/// must come out byte-for-byte the same on every run. Anyone who knows the
+/// seed can recompute the toxic waste of a setup or the blinding of a
+/// proof, so never use it for real keys or proofs.
+pub fn seeded(seed: u64) -> ChaCha20Rng {
+    ChaCha20Rng::seed_from_u64(seed)
+}
+
+/// The randomness behind setup and proving: the operating system's
+/// generator by default, or a seeded one for reproducible output.
+pub enum Randomness {
+    Os(OsRng),
+    Seeded(Box<ChaCha20Rng>),
+}
+
+impl Randomness {
+    /// `OsRng` unless a seed is given.
+    pub fn new(seed: Option<u64>) -> Self {
+        match seed {
+            Some(seed) => Randomness::Seeded(Box::new(seeded(seed))),
+            None => Randomness::Os(OsRng),
+        }
+    }
+}
+
+impl RngCore for Randomness {
+    fn next_u32(&mut self) -> u32 {
+        match self {
+            Randomness::Os(rng) => rng.next_u32(),
+            Randomness::Seeded(rng) => rng.next_u32(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            Randomness::Os(rng) => rng.next_u64(),
+            Randomness::Seeded(rng) => rng.next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        match self {
+            Randomness::Os(rng) => rng.fill_bytes(dest),
+            Randomness::Seeded(rng) => rng.fill_bytes(dest),
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        match self {
+            Randomness::Os(rng) => rng.try_fill_bytes(dest),
+            Randomness::Seeded(rng) => rng.try_fill_bytes(dest),
+        }
+    }
+}
+
+impl CryptoRng for Randomness {}
+
+#[test]
+fn test_seeded() {
+    use crate::registry;
+
+    let spec = registry::lookup("cube").unwrap();
+    let run = |seed| {
+        let mut rng = Randomness::new(Some(seed));
+        let params = spec.parameters(&mut rng).unwrap();
+        let witness = spec.sample_witness(&mut rng);
+        let (proof, _) = spec.prove(&params, &witness, &mut rng).unwrap();
+        (params, proof)
+    };
+
+    assert!(run(7) == run(7));
+    assert!(run(7) != run(8));
+
+    let mut os = Randomness::new(None);
+    assert!(matches!(os, Randomness::Os(_)));
+    assert_ne!(os.next_u64(), os.next_u64());
+}
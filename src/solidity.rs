@@ -0,0 +1,385 @@
+use std::fmt::Write as FmtWrite;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use bellman::groth16::{Proof, VerifyingKey};
+use bls12_381::{Bls12, G1Affine, G2Affine, Scalar};
+use ff::PrimeField;
+use sha3::{Digest, Keccak256};
+
+use crate::encode::encode_hex;
+use crate::error::{Error, Result};
+
+/// Signature of the generated verifier's entry point.
+pub const VERIFY_SIGNATURE: &str = "verifyProof(bytes,uint256[])";
+
+/// Size of an EIP-2537 G1 point: two 64-byte base field elements.
+pub const G1_SIZE: usize = 128;
+
+/// Size of an EIP-2537 G2 point: two 128-byte `Fp2` elements.
+pub const G2_SIZE: usize = 256;
+
+/// Size of the proof bytes passed to `verifyProof`: A, B and C.
+pub const PROOF_SIZE: usize = 2 * G1_SIZE + G2_SIZE;
+
+/// Pads a 48-byte big-endian base field element to the 64 bytes EIP-2537 uses.
+fn fp_to_eip2537(out: &mut [u8], bytes: &[u8]) {
+    out[16..64].copy_from_slice(bytes);
+}
+
+/// EIP-2537 encoding of a G1 point, `x || y`. The point at infinity is all zeros.
+pub fn g1_to_eip2537(p: &G1Affine) -> [u8; G1_SIZE] {
+    let mut out = [0u8; G1_SIZE];
+    if bool::from(p.is_identity()) {
+        return out;
+    }
+
+    let bytes = p.to_uncompressed();
+    fp_to_eip2537(&mut out[..64], &bytes[..48]);
+    fp_to_eip2537(&mut out[64..], &bytes[48..]);
+    out
+}
+
+/// EIP-2537 encoding of a G2 point, `x.c0 || x.c1 || y.c0 || y.c1`. The
+/// uncompressed bellman encoding stores `c1` first, so each pair is swapped.
+pub fn g2_to_eip2537(p: &G2Affine) -> [u8; G2_SIZE] {
+    let mut out = [0u8; G2_SIZE];
+    if bool::from(p.is_identity()) {
+        return out;
+    }
+
+    let bytes = p.to_uncompressed();
+    fp_to_eip2537(&mut out[..64], &bytes[48..96]);
+    fp_to_eip2537(&mut out[64..128], &bytes[..48]);
+    fp_to_eip2537(&mut out[128..192], &bytes[144..]);
+    fp_to_eip2537(&mut out[192..], &bytes[96..144]);
+    out
+}
+
+/// Big-endian 32-byte word of a scalar, as an EVM `uint256`.
+pub fn scalar_to_word(s: &Scalar) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word.copy_from_slice(s.to_repr().as_ref());
+    word.reverse();
+    word
+}
+
+/// The `proof` argument of `verifyProof`: A, B and C in EIP-2537 encoding.
+pub fn proof_to_eip2537(proof: &Proof<Bls12>) -> Vec<u8> {
+    let mut out = Vec::with_capacity(PROOF_SIZE);
+    out.extend_from_slice(&g1_to_eip2537(&proof.a));
+    out.extend_from_slice(&g2_to_eip2537(&proof.b));
+    out.extend_from_slice(&g1_to_eip2537(&proof.c));
+    out
+}
+
+/// First four bytes of the Keccak-256 hash of a function signature.
+pub fn selector(signature: &str) -> [u8; 4] {
+    let hash = Keccak256::digest(signature.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+fn word(n: usize) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&(n as u64).to_be_bytes());
+    word
+}
+
+/// ABI-encoded call to `verifyProof(bytes,uint256[])` for a proof and its
+/// public inputs.
+pub fn encode_calldata(proof: &Proof<Bls12>, inputs: &[Scalar]) -> Vec<u8> {
+    let proof = proof_to_eip2537(proof);
+
+    let mut out = selector(VERIFY_SIGNATURE).to_vec();
+    // Offsets of the two dynamic arguments, counted from the end of the selector.
+    // `PROOF_SIZE` is a multiple of 32, so the bytes need no padding.
+    out.extend_from_slice(&word(64));
+    out.extend_from_slice(&word(64 + 32 + PROOF_SIZE));
+    out.extend_from_slice(&word(PROOF_SIZE));
+    out.extend_from_slice(&proof);
+    out.extend_from_slice(&word(inputs.len()));
+    for input in inputs {
+        out.extend_from_slice(&scalar_to_word(input));
+    }
+    out
+}
+
+fn hex_literal(bytes: &[u8]) -> String {
+    format!("hex\"{}\"", encode_hex(bytes))
+}
+
+/// `cube` becomes `CubeVerifier`, `merkle_tree` becomes `MerkleTreeVerifier`.
+/// Characters other than ASCII letters and digits only separate words, and
+/// a name starting with a digit gets a `Circuit` prefix, so `2-of-3`
+/// becomes `Circuit2Of3Verifier`.
+pub fn contract_name(circuit: &str) -> Result<String> {
+    let mut name: String = circuit
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect();
+    if name.is_empty() {
+        return Err(Error::InvalidFormat(format!(
+            "`{}` has no letters or digits to name a contract after",
+            circuit
+        )));
+    }
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert_str(0, "Circuit");
+    }
+    name.push_str("Verifier");
+    Ok(name)
+}
+
+/// Fails unless `name` is a valid Solidity identifier.
+fn check_identifier(name: &str) -> Result<()> {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if !valid {
+        return Err(Error::InvalidFormat(format!(
+            "`{}` is not a valid contract name",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// Solidity source of a Groth16 verifier contract for one verifying key.
+///
+/// The contract checks `e(A, B) * e(alpha, -beta) * e(vk_x, -gamma) *
+/// e(C, -delta) == 1` with the EIP-2537 pairing precompile, where
+/// `vk_x = IC[0] + sum(input[i] * IC[i + 1])` comes from the G1 MSM
+/// precompile. The negated key points are computed here, so the contract
+/// never does base field arithmetic. Fails if the key has no IC points.
+pub fn generate_verifier(vk: &VerifyingKey<Bls12>, contract_name: &str) -> Result<String> {
+    let n_public = vk.ic.len().checked_sub(1).ok_or_else(|| {
+        Error::InvalidFormat("IC: a verifying key needs at least one point".to_string())
+    })?;
+
+    let mut ic_constants = String::new();
+    for (i, ic) in vk.ic.iter().enumerate() {
+        writeln!(
+            ic_constants,
+            "    bytes constant IC{} = {};",
+            i,
+            hex_literal(&g1_to_eip2537(ic))
+        )
+        .unwrap();
+    }
+
+    let mut msm_terms = String::from("            IC0, bytes32(uint256(1))");
+    for i in 0..n_public {
+        write!(
+            msm_terms,
+            ",\n            IC{}, bytes32(input[{}])",
+            i + 1,
+            i
+        )
+        .unwrap();
+    }
+
+    Ok(format!(
+        r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.24;
+
+/// @title Groth16 verifier over BLS12-381
+/// @notice Generated by bellman-example2. Needs the EIP-2537 precompiles.
+contract {name} {{
+    /// Order of the BLS12-381 scalar field.
+    uint256 constant R = 0x73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001;
+    address constant G1_MSM = address(0x0c);
+    address constant PAIRING_CHECK = address(0x0f);
+
+    uint256 constant N_PUBLIC = {n_public};
+
+    bytes constant ALPHA = {alpha};
+    bytes constant NEG_BETA = {neg_beta};
+    bytes constant NEG_GAMMA = {neg_gamma};
+    bytes constant NEG_DELTA = {neg_delta};
+{ic_constants}
+    /// @param proof A (128 bytes), B (256 bytes) and C (128 bytes) in EIP-2537 encoding
+    /// @param input The public inputs, each below R
+    function verifyProof(bytes calldata proof, uint256[] calldata input) public view returns (bool) {{
+        require(proof.length == {proof_size}, "bad proof length");
+        require(input.length == N_PUBLIC, "bad input length");
+        for (uint256 i = 0; i < N_PUBLIC; i++) {{
+            require(input[i] < R, "input not in field");
+        }}
+
+        bytes memory msmInput = bytes.concat(
+{msm_terms}
+        );
+        (bool ok, bytes memory vkX) = G1_MSM.staticcall(msmInput);
+        if (!ok || vkX.length != {g1_size}) {{
+            return false;
+        }}
+
+        bytes memory pairingInput = bytes.concat(
+            proof[0:{b_start}], proof[{b_start}:{c_start}],
+            ALPHA, NEG_BETA,
+            vkX, NEG_GAMMA,
+            proof[{c_start}:{proof_size}], NEG_DELTA
+        );
+        bytes memory out;
+        (ok, out) = PAIRING_CHECK.staticcall(pairingInput);
+        return ok && out.length == 32 && abi.decode(out, (uint256)) == 1;
+    }}
+}}
+"#,
+        name = contract_name,
+        n_public = n_public,
+        alpha = hex_literal(&g1_to_eip2537(&vk.alpha_g1)),
+        neg_beta = hex_literal(&g2_to_eip2537(&-vk.beta_g2)),
+        neg_gamma = hex_literal(&g2_to_eip2537(&-vk.gamma_g2)),
+        neg_delta = hex_literal(&g2_to_eip2537(&-vk.delta_g2)),
+        ic_constants = ic_constants,
+        proof_size = PROOF_SIZE,
+        msm_terms = msm_terms,
+        g1_size = G1_SIZE,
+        b_start = G1_SIZE,
+        c_start = G1_SIZE + G2_SIZE,
+    ))
+}
+
+pub fn write_verifier<W: Write>(
+    vk: &VerifyingKey<Bls12>,
+    contract_name: &str,
+    mut writer: W,
+) -> Result<()> {
+    check_identifier(contract_name)?;
+    writer.write_all(generate_verifier(vk, contract_name)?.as_bytes())?;
+    Ok(())
+}
+
+/// Writes the calldata as a single `0x`-prefixed hex string.
+pub fn write_calldata<W: Write>(
+    proof: &Proof<Bls12>,
+    inputs: &[Scalar],
+    mut writer: W,
+) -> Result<()> {
+    write!(writer, "0x{}", encode_hex(&encode_calldata(proof, inputs)))?;
+    Ok(())
+}
+
+pub fn create_verifier_file<P: AsRef<Path>>(
+    path: P,
+    vk: &VerifyingKey<Bls12>,
+    contract_name: &str,
+) -> Result<()> {
+    write_verifier(vk, contract_name, File::create(path)?)
+}
+
+pub fn create_calldata_file<P: AsRef<Path>>(
+    path: P,
+    proof: &Proof<Bls12>,
+    inputs: &[Scalar],
+) -> Result<()> {
+    write_calldata(proof, inputs, File::create(path)?)
+}
+
+#[test]
+fn test_eip2537_encoding() {
+    let g1 = g1_to_eip2537(&G1Affine::generator());
+    assert_eq!(&g1[..16], &[0u8; 16]);
+    assert_eq!(&g1[16..64], &G1Affine::generator().to_uncompressed()[..48]);
+    assert_eq!(g1_to_eip2537(&G1Affine::identity()), [0u8; G1_SIZE]);
+
+    // x.c0 of the G2 generator leads the encoding.
+    let g2 = g2_to_eip2537(&G2Affine::generator());
+    assert_eq!(
+        &g2[16..64],
+        &G2Affine::generator().to_uncompressed()[48..96]
+    );
+    assert_eq!(&g2[80..128], &G2Affine::generator().to_uncompressed()[..48]);
+
+    assert_eq!(scalar_to_word(&Scalar::from(35u64))[31], 35);
+    assert_eq!(
+        selector("transfer(address,uint256)"),
+        [0xa9, 0x05, 0x9c, 0xbb]
+    );
+}
+
+#[test]
+fn test_verifier_pairing_equation() {
+    use bls12_381::{multi_miller_loop, G1Projective, G2Prepared, Gt};
+
+    use crate::cube;
+    use crate::rng::seeded;
+
+    let (params, proof, _) = cube::test_proof(&mut seeded(0));
+    let vk = &params.vk;
+
+    // The same check the contract runs through the precompiles.
+    let check = |input: Scalar| {
+        let vk_x = G1Affine::from(G1Projective::from(vk.ic[0]) + vk.ic[1] * input);
+        let neg_beta = G2Prepared::from(-vk.beta_g2);
+        let neg_gamma = G2Prepared::from(-vk.gamma_g2);
+        let neg_delta = G2Prepared::from(-vk.delta_g2);
+        let b = G2Prepared::from(proof.b);
+        multi_miller_loop(&[
+            (&proof.a, &b),
+            (&vk.alpha_g1, &neg_beta),
+            (&vk_x, &neg_gamma),
+            (&proof.c, &neg_delta),
+        ])
+        .final_exponentiation()
+            == Gt::identity()
+    };
+    assert!(check(Scalar::from(35u64)));
+    assert!(!check(Scalar::from(36u64)));
+
+    let source = generate_verifier(vk, "CubeVerifier").unwrap();
+    assert!(source.contains("contract CubeVerifier {"));
+    assert!(source.contains("uint256 constant N_PUBLIC = 1;"));
+    assert!(source.contains("IC1, bytes32(input[0])"));
+    assert!(source.contains(&hex_literal(&g2_to_eip2537(&-vk.delta_g2))));
+
+    let calldata = encode_calldata(&proof, &[Scalar::from(35u64)]);
+    assert_eq!(calldata.len(), 4 + 3 * 32 + PROOF_SIZE + 2 * 32);
+    assert_eq!(
+        &calldata[4 + 96..4 + 96 + PROOF_SIZE],
+        &proof_to_eip2537(&proof)[..]
+    );
+    assert_eq!(calldata[calldata.len() - 1], 35);
+}
+
+#[test]
+fn test_contract_name() {
+    assert_eq!(contract_name("cube").unwrap(), "CubeVerifier");
+    assert_eq!(contract_name("merkle_tree").unwrap(), "MerkleTreeVerifier");
+    assert_eq!(contract_name("2-of-3").unwrap(), "Circuit2Of3Verifier");
+    assert_eq!(contract_name("h\u{e9}llo").unwrap(), "HLloVerifier");
+    assert!(contract_name("").is_err());
+    assert!(contract_name("--").is_err());
+
+    let vk = crate::cube::test_proof(&mut crate::rng::seeded(0)).0.vk;
+    assert!(write_verifier(&vk, "CubeVerifier", vec![]).is_ok());
+    for name in ["", "2Verifier", "Cube Verifier", "Cube-Verifier"] {
+        assert!(write_verifier(&vk, name, vec![]).is_err(), "{}", name);
+    }
+
+    // The function the contract exposes is the one calldata is encoded for;
+    // the selector was computed with an independent Keccak-256.
+    assert_eq!(selector(VERIFY_SIGNATURE), [0x1e, 0x8e, 0x1e, 0x13]);
+    let source = generate_verifier(&vk, "CubeVerifier").unwrap();
+    assert!(source.contains(
+        "function verifyProof(bytes calldata proof, uint256[] calldata input) public view returns (bool)"
+    ));
+
+    // A key without IC points has no constant term to start from.
+    let mut empty = vk;
+    empty.ic.clear();
+    assert!(matches!(
+        generate_verifier(&empty, "CubeVerifier"),
+        Err(Error::InvalidFormat(_))
+    ));
+}